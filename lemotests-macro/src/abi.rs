@@ -0,0 +1,711 @@
+use crate::{
+    ArgumentSchema, ContractSchema, FunctionKind, FunctionSchema, MacrosError, Serializer,
+    TypeSchema, VariantSchema,
+};
+use lemotests::abi::{
+    AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiParameters, AbiRoot, AbiType,
+};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Type of arguments which don't have a Rust counterpart, e.g. maps or untagged enums.
+pub(crate) const VALUE_TYPE: &str = "lemotests::serde_json::Value";
+
+/// Depth of nested `$ref` which is resolved before falling back to `serde_json::Value`.
+const MAX_REF_DEPTH: usize = 16;
+
+/// Converts the ABI generated by near-sdk into the schema.
+/// The contract is named after the package in ABI metadata or after the file.
+pub(crate) fn contract_schema_from_abi(
+    abi: &AbiRoot,
+    path: &Path,
+) -> Result<ContractSchema, MacrosError> {
+    let name = abi
+        .metadata
+        .name
+        .clone()
+        .or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default()
+        .replace('-', "_");
+
+    let mut problems = Vec::new();
+    let mut functions = Vec::new();
+    for function in &abi.body.functions {
+        match function_schema(abi, function) {
+            Ok(function) => functions.push(function),
+            Err(problem) => problems.push(problem),
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(MacrosError::InvalidSchemaError(problems.join("; ")));
+    }
+
+    Ok(ContractSchema {
+        name,
+        functions,
+        types: type_schemas(abi),
+    })
+}
+
+/// Generates types for definitions which are used by JSON arguments and results, directly or through other definitions.
+fn type_schemas(abi: &AbiRoot) -> Vec<TypeSchema> {
+    let mut queue = Vec::new();
+    for function in &abi.body.functions {
+        if let AbiParameters::Json { args } = &function.params {
+            for arg in args {
+                collect_refs(&arg.type_schema, &mut queue);
+            }
+        }
+        if let Some(AbiType::Json { type_schema }) = &function.result {
+            collect_refs(type_schema, &mut queue);
+        }
+    }
+
+    let mut used = BTreeSet::new();
+    while let Some(reference) = queue.pop() {
+        if used.insert(reference.clone()) {
+            if let Some(definition) = abi.definition(&reference) {
+                collect_refs(definition, &mut queue);
+            }
+        }
+    }
+
+    used.iter()
+        .filter_map(|reference| {
+            let name = definition_name(reference);
+            let definition = abi.definition(reference)?;
+            is_type_definition(name, definition).then(|| type_schema(abi, name, definition))
+        })
+        .collect()
+}
+
+fn collect_refs(schema: &Value, refs: &mut Vec<String>) {
+    match schema {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => refs.push(reference.clone()),
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
+
+fn definition_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or_default()
+}
+
+/// Types of near-sdk which are mapped into `lemotests` types instead of being generated.
+fn known_type(name: &str) -> Option<&'static str> {
+    match name {
+        "U128" => Some("lemotests::YoctoNear"),
+        "Gas" => Some("lemotests::Gas"),
+        "U64" | "AccountId" | "TokenId" => Some("String"),
+        _ => None,
+    }
+}
+
+/// Definitions which are generated as types: structs with named fields and externally tagged enums.
+fn is_type_definition(name: &str, definition: &Value) -> bool {
+    known_type(name).is_none()
+        && crate::is_type_name(name)
+        && (struct_fields(definition).is_some() || enum_variants(definition).is_some())
+}
+
+fn type_schema(abi: &AbiRoot, name: &str, definition: &Value) -> TypeSchema {
+    if let Some(properties) = struct_fields(definition) {
+        return TypeSchema::Struct {
+            name: name.to_owned(),
+            fields: fields(abi, name, definition, properties),
+        };
+    }
+
+    let variants = enum_variants(definition)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|variant| match variant {
+            Variant::Unit(variant) => VariantSchema {
+                name: variant.to_owned(),
+                fields: None,
+                r#type: None,
+            },
+            Variant::Struct(variant, schema, properties) => VariantSchema {
+                name: variant.to_owned(),
+                fields: Some(fields(abi, name, schema, properties)),
+                r#type: None,
+            },
+            Variant::Newtype(variant, schema) => VariantSchema {
+                name: variant.to_owned(),
+                fields: None,
+                r#type: Some(boxed_if_recursive(name, json_type(abi, schema, 0))),
+            },
+        })
+        .collect();
+    TypeSchema::Enum {
+        name: name.to_owned(),
+        variants,
+    }
+}
+
+/// Fields which aren't required are optional.
+/// `owner` is the name of the generated type, fields which reference it directly are boxed.
+fn fields(
+    abi: &AbiRoot,
+    owner: &str,
+    schema: &Value,
+    properties: &Map<String, Value>,
+) -> Vec<ArgumentSchema> {
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let r#type = json_type(abi, property, 0);
+            let optional = !required.iter().any(|field| field == name);
+            let r#type = if optional && !r#type.starts_with("Option<") {
+                format!("Option<{type}>")
+            } else {
+                r#type
+            };
+            ArgumentSchema {
+                name: name.clone(),
+                r#type: boxed_if_recursive(owner, r#type),
+            }
+        })
+        .collect()
+}
+
+/// Boxes the direct reference of the type to itself, e.g. `next: Option<Node>` of `Node`,
+/// which would give the type of infinite size. References through `Vec` don't need it.
+fn boxed_if_recursive(owner: &str, ty: String) -> String {
+    if ty == owner {
+        format!("Box<{owner}>")
+    } else if ty == format!("Option<{owner}>") {
+        format!("Option<Box<{owner}>>")
+    } else {
+        ty
+    }
+}
+
+fn struct_fields(schema: &Value) -> Option<&Map<String, Value>> {
+    if schema.get("type").and_then(Value::as_str) != Some("object") {
+        return None;
+    }
+
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .filter(|properties| properties.keys().all(|name| crate::is_field_name(name)))
+}
+
+enum Variant<'a> {
+    Unit(&'a str),
+    Struct(&'a str, &'a Value, &'a Map<String, Value>),
+    Newtype(&'a str, &'a Value),
+}
+
+/// Variants of the enum as serde represents them by default:
+/// unit variants are strings and other variants are objects with the single key.
+fn enum_variants(schema: &Value) -> Option<Vec<Variant<'_>>> {
+    if let Some(names) = unit_variants(schema) {
+        return Some(names.into_iter().map(Variant::Unit).collect());
+    }
+
+    let mut ret = Vec::new();
+    for variant in schema.get("oneOf")?.as_array()? {
+        if let Some(names) = unit_variants(variant) {
+            ret.extend(names.into_iter().map(Variant::Unit));
+            continue;
+        }
+
+        let properties = struct_fields(variant)?;
+        let required = variant.get("required")?.as_array()?;
+        let (name, inner) = match (properties.iter().next(), required.as_slice()) {
+            (Some((name, inner)), [Value::String(key)])
+                if properties.len() == 1 && name == key && crate::is_type_name(name) =>
+            {
+                (name.as_str(), inner)
+            }
+            _ => return None,
+        };
+        ret.push(match struct_fields(inner) {
+            Some(fields) => Variant::Struct(name, inner, fields),
+            None => Variant::Newtype(name, inner),
+        });
+    }
+
+    (!ret.is_empty()).then_some(ret)
+}
+
+fn unit_variants(schema: &Value) -> Option<Vec<&str>> {
+    schema
+        .get("enum")?
+        .as_array()?
+        .iter()
+        .map(|name| name.as_str().filter(|name| crate::is_type_name(name)))
+        .collect()
+}
+
+fn function_schema(abi: &AbiRoot, function: &AbiFunction) -> Result<FunctionSchema, String> {
+    let serializer = match function.params {
+        AbiParameters::Json { .. } => Serializer::Json,
+        AbiParameters::Borsh { .. } => Serializer::Borsh,
+    };
+
+    let arguments = function
+        .params
+        .args()
+        .iter()
+        .map(|arg| {
+            let r#type = match serializer {
+                Serializer::Json => Ok(json_type(abi, &arg.type_schema, 0)),
+                Serializer::Borsh => borsh_type(&arg.type_schema),
+            }
+            .map_err(|problem| {
+                format!(
+                    "argument `{}` of function `{}`: {problem}",
+                    arg.name, function.name
+                )
+            })?;
+
+            Ok(ArgumentSchema {
+                name: arg.name.clone(),
+                r#type,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    // results which can't be expressed are left untyped
+    let (returns, result_serializer) = match &function.result {
+        Some(AbiType::Json { type_schema }) => {
+            (Some(json_type(abi, type_schema, 0)), Serializer::Json)
+        }
+        Some(AbiType::Borsh { type_schema }) => (borsh_type(type_schema).ok(), Serializer::Borsh),
+        None => (None, Serializer::Json),
+    };
+
+    Ok(FunctionSchema {
+        name: function.name.clone(),
+        kind: match function.kind {
+            AbiFunctionKind::Call => FunctionKind::Call,
+            AbiFunctionKind::View => FunctionKind::View,
+        },
+        serializer,
+        arguments,
+        doc: function.doc.clone(),
+        initable: function.has_modifier(AbiFunctionModifier::Init),
+        private: function.has_modifier(AbiFunctionModifier::Private),
+        payable: Some(function.has_modifier(AbiFunctionModifier::Payable)),
+        returns: returns.filter(|ty| ty != "()"),
+        result_serializer,
+    })
+}
+
+/// Maps JSON schema of the argument into the Rust type.
+/// Definitions of structs and enums become generated types, other types which can't be expressed become `serde_json::Value`.
+fn json_type(abi: &AbiRoot, schema: &Value, depth: usize) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = definition_name(reference);
+        if let Some(ty) = known_type(name) {
+            return ty.to_owned();
+        }
+        return match abi.definition(reference) {
+            Some(definition) if is_type_definition(name, definition) => name.to_owned(),
+            Some(definition) if depth < MAX_REF_DEPTH => json_type(abi, definition, depth + 1),
+            _ => VALUE_TYPE.to_owned(),
+        };
+    }
+
+    for key in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            return match variants.as_slice() {
+                [some, none] | [none, some] if is_null(none) => {
+                    format!("Option<{}>", json_type(abi, some, depth))
+                }
+                _ => VALUE_TYPE.to_owned(),
+            };
+        }
+    }
+
+    if let Some([single]) = schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+    {
+        return json_type(abi, single, depth);
+    }
+
+    match schema.get("type") {
+        Some(Value::String(ty)) => primitive_json_type(abi, schema, ty, depth),
+        Some(Value::Array(types)) => match types.as_slice() {
+            [Value::String(ty), Value::String(null)] | [Value::String(null), Value::String(ty)]
+                if null == "null" =>
+            {
+                format!("Option<{}>", primitive_json_type(abi, schema, ty, depth))
+            }
+            _ => VALUE_TYPE.to_owned(),
+        },
+        _ => VALUE_TYPE.to_owned(),
+    }
+}
+
+fn primitive_json_type(abi: &AbiRoot, schema: &Value, ty: &str, depth: usize) -> String {
+    match ty {
+        "string" => "String".to_owned(),
+        "boolean" => "bool".to_owned(),
+        "number" => "f64".to_owned(),
+        "null" => "()".to_owned(),
+        "integer" => {
+            let format = schema.get("format").and_then(Value::as_str);
+            let unsigned = schema
+                .get("minimum")
+                .and_then(Value::as_f64)
+                .is_some_and(|minimum| minimum >= 0.0);
+            match format {
+                Some("uint8") => "u8",
+                Some("uint16") => "u16",
+                Some("uint32" | "uint") => "u32",
+                Some("uint64") => "u64",
+                Some("int8") => "i8",
+                Some("int16") => "i16",
+                Some("int32" | "int") => "i32",
+                Some("int64") => "i64",
+                _ if unsigned => "u64",
+                _ => "i64",
+            }
+            .to_owned()
+        }
+        "array" => match schema.get("items") {
+            Some(Value::Array(items)) => {
+                let items = items
+                    .iter()
+                    .map(|item| json_type(abi, item, depth))
+                    .collect::<Vec<_>>();
+                format!("({},)", items.join(", "))
+            }
+            Some(item) => format!("Vec<{}>", json_type(abi, item, depth)),
+            None => format!("Vec<{VALUE_TYPE}>"),
+        },
+        _ => VALUE_TYPE.to_owned(),
+    }
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+/// Maps the declaration of borsh schema into the Rust type.
+/// Only primitives and their collections are supported, because arguments must implement `BorshSerialize`.
+fn borsh_type(schema: &Value) -> Result<String, String> {
+    let declaration = schema
+        .get("declaration")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "borsh schema doesn't have a declaration".to_owned())?;
+
+    borsh_declaration_type(declaration)
+        .ok_or_else(|| format!("borsh type `{declaration}` isn't supported"))
+}
+
+fn borsh_declaration_type(declaration: &str) -> Option<String> {
+    let declaration = declaration.trim();
+    match declaration {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" | "bool"
+        | "()" => return Some(declaration.to_owned()),
+        "string" | "String" => return Some("String".to_owned()),
+        _ => {}
+    }
+
+    for collection in ["Vec", "Option"] {
+        let inner = declaration
+            .strip_prefix(collection)
+            .and_then(|rest| rest.strip_prefix('<'))
+            .and_then(|rest| rest.strip_suffix('>'));
+        if let Some(inner) = inner {
+            return Some(format!("{collection}<{}>", borsh_declaration_type(inner)?));
+        }
+    }
+
+    let (item, len) = declaration
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .rsplit_once(';')?;
+    let len = len.trim().parse::<usize>().ok()?;
+    Some(format!("[{}; {len}]", borsh_declaration_type(item)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn abi() -> AbiRoot {
+        serde_json::from_value(json!({
+            "schema_version": "0.3.0",
+            "metadata": { "name": "nft-token" },
+            "body": {
+                "functions": [
+                    {
+                        "name": "nft_mint",
+                        "doc": " Mints the token.",
+                        "kind": "call",
+                        "modifiers": ["payable"],
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [
+                                { "name": "token_id", "type_schema": { "$ref": "#/definitions/TokenId" } },
+                                { "name": "amount", "type_schema": { "$ref": "#/definitions/U128" } },
+                                { "name": "memo", "type_schema": { "type": ["string", "null"] } },
+                                { "name": "metadata", "type_schema": { "$ref": "#/definitions/TokenMetadata" } }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "set_counter",
+                        "kind": "call",
+                        "modifiers": ["private"],
+                        "params": {
+                            "serialization_type": "borsh",
+                            "args": [{ "name": "value", "type_schema": { "declaration": "Vec<u64>", "definitions": {} } }]
+                        }
+                    },
+                    {
+                        "name": "nft_token",
+                        "kind": "view",
+                        "result": {
+                            "serialization_type": "json",
+                            "type_schema": { "anyOf": [{ "$ref": "#/definitions/TokenMetadata" }, { "type": "null" }] }
+                        }
+                    }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "TokenMetadata": {
+                            "type": "object",
+                            "required": ["title", "status"],
+                            "properties": {
+                                "title": { "type": "string" },
+                                "copies": { "type": "integer", "format": "uint64", "minimum": 0 },
+                                "status": { "$ref": "#/definitions/Status" },
+                                "extra": { "type": "object", "additionalProperties": { "type": "string" } }
+                            }
+                        },
+                        "Status": {
+                            "oneOf": [
+                                { "type": "string", "enum": ["Draft"] },
+                                {
+                                    "type": "object",
+                                    "required": ["Listed"],
+                                    "properties": { "Listed": { "$ref": "#/definitions/U128" } },
+                                    "additionalProperties": false
+                                },
+                                {
+                                    "type": "object",
+                                    "required": ["Sold"],
+                                    "properties": {
+                                        "Sold": {
+                                            "type": "object",
+                                            "required": ["buyer"],
+                                            "properties": { "buyer": { "$ref": "#/definitions/AccountId" } }
+                                        }
+                                    },
+                                    "additionalProperties": false
+                                }
+                            ]
+                        },
+                        "Unused": { "type": "string", "enum": ["A", "B"] }
+                    }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn abi_is_converted() {
+        let schema = contract_schema_from_abi(&abi(), Path::new("nft.json")).unwrap();
+        assert_eq!(schema.name, "nft_token");
+
+        let mint = &schema.functions[0];
+        assert!(mint.payable == Some(true) && !mint.private && !mint.initable);
+        let types = mint
+            .arguments
+            .iter()
+            .map(|arg| arg.r#type.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                "String",
+                "lemotests::YoctoNear",
+                "Option<String>",
+                "TokenMetadata"
+            ]
+        );
+
+        let set_counter = &schema.functions[1];
+        assert!(set_counter.private);
+        assert!(matches!(set_counter.serializer, Serializer::Borsh));
+        assert_eq!(set_counter.arguments[0].r#type, "Vec<u64>");
+        assert_eq!(set_counter.returns, None);
+
+        let nft_token = &schema.functions[2];
+        assert_eq!(nft_token.returns.as_deref(), Some("Option<TokenMetadata>"));
+    }
+
+    #[test]
+    fn definitions_are_generated() {
+        let schema = contract_schema_from_abi(&abi(), Path::new("nft.json")).unwrap();
+        assert_eq!(schema.types.len(), 2);
+
+        let TypeSchema::Enum { name, variants } = &schema.types[0] else {
+            panic!("`Status` must be an enum");
+        };
+        assert_eq!(name, "Status");
+        let variants = variants
+            .iter()
+            .map(|variant| {
+                let fields = variant.fields.as_ref().map(|fields| {
+                    fields
+                        .iter()
+                        .map(|field| format!("{}: {}", field.name, field.r#type))
+                        .collect::<Vec<_>>()
+                });
+                (variant.name.as_str(), fields, variant.r#type.as_deref())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            variants,
+            [
+                ("Draft", None, None),
+                ("Listed", None, Some("lemotests::YoctoNear")),
+                ("Sold", Some(vec!["buyer: String".to_owned()]), None),
+            ]
+        );
+
+        let TypeSchema::Struct { name, fields } = &schema.types[1] else {
+            panic!("`TokenMetadata` must be a struct");
+        };
+        assert_eq!(name, "TokenMetadata");
+        let fields = fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name, field.r#type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                "copies: Option<u64>",
+                format!("extra: Option<{VALUE_TYPE}>").as_str(),
+                "status: Status",
+                "title: String",
+            ]
+        );
+    }
+
+    #[test]
+    fn json_types_are_mapped() {
+        let abi = abi();
+        let cases = [
+            (
+                json!({ "type": "integer", "format": "uint32", "minimum": 0 }),
+                "u32",
+            ),
+            (json!({ "type": "integer", "minimum": 0 }), "u64"),
+            (
+                json!({ "type": "array", "items": { "type": "boolean" } }),
+                "Vec<bool>",
+            ),
+            (
+                json!({ "type": "array", "items": [{ "type": "string" }, { "type": "number" }] }),
+                "(String, f64,)",
+            ),
+            (
+                json!({ "anyOf": [{ "$ref": "#/definitions/U64" }, { "type": "null" }] }),
+                "Option<String>",
+            ),
+            (json!({ "$ref": "#/definitions/Gas" }), "lemotests::Gas"),
+        ];
+
+        for (schema, expected) in cases {
+            assert_eq!(json_type(&abi, &schema, 0), expected);
+        }
+    }
+
+    #[test]
+    fn unsupported_borsh_types_are_reported() {
+        assert_eq!(
+            borsh_type(&json!({ "declaration": "[u8; 32]" })).unwrap(),
+            "[u8; 32]"
+        );
+        assert_eq!(
+            borsh_type(&json!({ "declaration": "HashMap<string, u64>" })).unwrap_err(),
+            "borsh type `HashMap<string, u64>` isn't supported"
+        );
+    }
+
+    #[test]
+    fn recursive_definitions_are_boxed() {
+        let abi: AbiRoot = serde_json::from_value(json!({
+            "schema_version": "0.3.0",
+            "metadata": {},
+            "body": {
+                "functions": [{
+                    "name": "push",
+                    "kind": "call",
+                    "params": {
+                        "serialization_type": "json",
+                        "args": [{ "name": "node", "type_schema": { "$ref": "#/definitions/Node" } }]
+                    }
+                }],
+                "root_schema": {
+                    "definitions": {
+                        "Node": {
+                            "type": "object",
+                            "required": ["tree", "children"],
+                            "properties": {
+                                "next": { "$ref": "#/definitions/Node" },
+                                "children": { "type": "array", "items": { "$ref": "#/definitions/Node" } },
+                                "tree": { "$ref": "#/definitions/Tree" }
+                            }
+                        },
+                        "Tree": {
+                            "oneOf": [
+                                { "type": "string", "enum": ["Leaf"] },
+                                {
+                                    "type": "object",
+                                    "required": ["Branch"],
+                                    "properties": { "Branch": { "$ref": "#/definitions/Tree" } },
+                                    "additionalProperties": false
+                                }
+                            ]
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let schema = contract_schema_from_abi(&abi, Path::new("list.json")).unwrap();
+        let types = schema
+            .types
+            .iter()
+            .map(|ty| ty.type_tt().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(types.len(), 2);
+        assert!(types[0].contains("pub children : Vec < Node >"));
+        assert!(types[0].contains("pub next : Option < Box < Node > >"));
+        assert!(types[0].contains("pub tree : Tree"));
+        assert!(types[1].contains("Branch (Box < Tree >)"));
+    }
+}
@@ -1,5 +1,7 @@
+use crate::Serializer;
 use quote::quote;
-use syn::{FnArg, Pat, PatIdent};
+use syn::ext::IdentExt;
+use syn::{FnArg, Pat, PatIdent, Type};
 use lemotests::TxKind;
 use proc_macro2::TokenStream;
 
@@ -10,6 +12,13 @@ pub(crate) struct FunctionBlueprint {
     pub(crate) account: Option<String>,
     pub(crate) tx_kind: TxKind,
     pub(crate) contract_name: String,
+    pub(crate) serializer: Serializer,
+    /// Lines of documentation of the generated method.
+    pub(crate) doc: Vec<String>,
+    /// The type of the result and how it is decoded.
+    pub(crate) returns: Option<(Type, Serializer)>,
+    /// Whether the function accepts a deposit, `None` if it's unknown.
+    pub(crate) payable: Option<bool>,
 }
 
 impl FunctionBlueprint {
@@ -20,6 +29,7 @@ impl FunctionBlueprint {
         account: Option<String>,
         tx_kind: TxKind,
         contract_name: String,
+        serializer: Serializer,
     ) -> Self {
         Self {
             contract_function_name: contract_function_name.clone(),
@@ -28,6 +38,48 @@ impl FunctionBlueprint {
             account,
             tx_kind,
             contract_name,
+            serializer,
+            doc: Vec::new(),
+            returns: None,
+            payable: None,
+        }
+    }
+
+    pub(crate) fn with_doc(mut self, doc: Vec<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    pub(crate) fn with_returns(mut self, returns: Option<Type>, serializer: Serializer) -> Self {
+        self.returns = returns.map(|ty| (ty, serializer));
+        self
+    }
+
+    pub(crate) fn with_payable(mut self, payable: Option<bool>) -> Self {
+        self.payable = payable;
+        self
+    }
+
+    /// Tokens which mark `__lemotests_tx` as not payable if the function doesn't accept deposits.
+    pub fn non_payable_tt(&self) -> TokenStream {
+        match self.payable {
+            Some(false) => quote!(let __lemotests_tx = __lemotests_tx.non_payable();),
+            _ => TokenStream::new(),
+        }
+    }
+
+    /// The return type of the method and the expression which converts `__lemotests_tx` into it.
+    pub fn return_type_tt(&self) -> (TokenStream, TokenStream) {
+        match &self.returns {
+            Some((ty, Serializer::Json)) => (
+                quote!(lemotests::TypedTx<T, #ty>),
+                quote!(lemotests::TypedTx::json(__lemotests_tx)),
+            ),
+            Some((ty, Serializer::Borsh)) => (
+                quote!(lemotests::TypedTx<T, #ty>),
+                quote!(lemotests::TypedTx::borsh(__lemotests_tx)),
+            ),
+            None => (quote!(lemotests::TxWrapper<T>), quote!(__lemotests_tx)),
         }
     }
 
@@ -50,4 +102,30 @@ impl FunctionBlueprint {
         let args = self.args.clone();
         quote!(#(#args),*)
     }
+
+    /// Tokens which serialize arguments of the method into `__lemotests_arguments` variable
+    /// of `lemotests::TxArguments` type. Locals are prefixed, so they don't shadow arguments.
+    pub fn serialized_args_tt(&self) -> TokenStream {
+        let args_without_types = self.args_without_types();
+        let arg_names = args_without_types
+            .iter()
+            .map(|arg| arg.ident.unraw().to_string());
+        match self.serializer {
+            Serializer::Json => quote! {
+                let mut __lemotests_json_args = lemotests::serde_json::Map::new();
+                #(
+                    let __lemotests_value = lemotests::serde_json::to_value(#args_without_types).expect("Fail to serialize argument to `Value`");
+                    __lemotests_json_args.insert(#arg_names.into(), __lemotests_value);
+                )*
+                let __lemotests_arguments = lemotests::TxArguments::Json(__lemotests_json_args);
+            },
+            Serializer::Borsh => quote! {
+                let mut __lemotests_borsh_args = Vec::new();
+                #(
+                    lemotests::borsh::BorshSerialize::serialize(&#args_without_types, &mut __lemotests_borsh_args).expect("Fail to serialize argument with borsh");
+                )*
+                let __lemotests_arguments = lemotests::TxArguments::Bytes(__lemotests_borsh_args);
+            },
+        }
+    }
 }
@@ -1,19 +1,63 @@
 use core::fmt;
+use proc_macro2::Span;
 use std::error::Error;
 use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
 
 #[derive(thiserror::Error)]
 pub enum MacrosError {
-    #[error("Failed to open file with json scheme. {0}")]
-    FailedToOpenFileError(#[from] std::io::Error),
-    #[error("Failed to deserialize json scheme. {0}")]
-    DeserializeJsonSchemaError(#[from] serde_json::Error),
+    #[error("Failed to open file with json scheme `{}`. {source}", path.display())]
+    FailedToOpenFileError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to deserialize json scheme `{}`: {source}", path.display())]
+    DeserializeJsonSchemaError {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Failed to extract ABI from wasm `{}`. {source}", path.display())]
+    ExtractAbiError {
+        path: PathBuf,
+        source: lemotests::HelperError,
+    },
+    #[error("Failed to parse contract source `{}`. {source}", path.display())]
+    ParseSourceError { path: PathBuf, source: syn::Error },
+    #[error("Failed to parse argument `{argument}: {ty}` of function `{function}`. {source}")]
+    InvalidArgumentError {
+        function: String,
+        argument: String,
+        ty: String,
+        source: syn::Error,
+    },
+    #[error("Failed to parse return type `{ty}` of function `{function}`. {source}")]
+    InvalidReturnTypeError {
+        function: String,
+        ty: String,
+        source: syn::Error,
+    },
+    #[error("Failed to parse field `{field}: {ty}` of type `{owner}`. {source}")]
+    InvalidFieldError {
+        owner: String,
+        field: String,
+        ty: String,
+        source: syn::Error,
+    },
     #[error("Failed to parse. {0}")]
     FailedToParseError(#[from] syn::Error),
+    #[error("Invalid json scheme: {0}")]
+    InvalidSchemaError(String),
     #[error("Failed to destructure `Punctuated`: {0}")]
     DestructuringPunctuatedError(String),
 }
 
+impl MacrosError {
+    /// Converts the error into `compile_error!` pointing at `span`.
+    pub(crate) fn into_syn_error(self, span: Span) -> syn::Error {
+        syn::Error::new(span, self)
+    }
+}
+
 impl Debug for MacrosError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         error_chain_fmt(self, f)
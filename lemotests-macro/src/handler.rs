@@ -1,72 +1,179 @@
 use crate::blueprint::FunctionBlueprint;
-use crate::{ContractSchema, MacrosError};
+use crate::{HelpersInput, SchemaSource, TypeSchema};
 use lemotests::consts::ACCOUNTS;
 use lemotests::TxKind;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
-use syn::parse::Parser;
-use syn::punctuated::Punctuated;
-use syn::{ExprLit, Lit, Token};
-
-pub(crate) fn handle_input_tt(input: proc_macro::TokenStream) -> Result<TokenStream, MacrosError> {
-    let parser = Punctuated::<ExprLit, Token![,]>::parse_terminated;
-    let parsed = parser.parse(input);
-    let tokens = match parsed {
-        Ok(punctuated) => {
-            let paths = get_paths(punctuated)?;
-            let mut readers = crate::read_json_schemas_from_file(&paths)?;
-            let schemas = crate::deserialize_json_schemas(&mut readers)?;
-            compose_helper_trait_tt(&schemas)
-        }
-        _ => syn::Error::new(
-            Span::call_site(),
-            "parse can only be used with list of literal paths",
-        )
-        .to_compile_error(),
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::LitStr;
+
+/// Parses paths to schemas and generates helpers.
+/// Errors are reported at the literal of the schema which has caused them.
+pub(crate) fn handle_input_tt(input: proc_macro::TokenStream) -> Result<TokenStream, syn::Error> {
+    let input = syn::parse::<HelpersInput>(input)?;
+    if let Some(aliases) = &input.accounts {
+        crate::validate_aliases(aliases)?;
+    }
+    let accounts = match &input.accounts {
+        Some(aliases) => aliases.iter().map(LitStr::value).collect(),
+        None => ACCOUNTS.map(str::to_owned).to_vec(),
     };
+    let accounts = accounts.iter().map(String::as_str).collect::<Vec<_>>();
 
-    Ok(tokens)
-}
+    let mut paths = Vec::new();
+    let mut contracts = Vec::new();
+    let mut trait_names = HashMap::new();
+    let mut method_names = HashMap::new();
+    let mut type_names = HashMap::new();
+    let mut errors: Option<syn::Error> = None;
+    for SchemaSource { lit, format } in &input.schemas {
+        let path = resolve_path(lit.value());
+        let ret = crate::read_json_schema(&path, *format)
+            .and_then(|schema| {
+                schema.validate()?;
+                crate::check_trait_collisions(&mut trait_names, &schema)?;
+                let blueprints = schema.blueprints(&accounts)?;
+                crate::check_name_collisions(&mut method_names, &blueprints)?;
+                let types = crate::check_type_collisions(&mut type_names, &schema)?
+                    .into_iter()
+                    .map(TypeSchema::type_tt)
+                    .collect::<Result<TokenStream, _>>()?;
+                Ok((schema.name, types, blueprints))
+            })
+            .map_err(|e| e.into_syn_error(lit.span()));
 
-fn get_paths<P>(punctuated: Punctuated<ExprLit, P>) -> Result<Vec<String>, MacrosError> {
-    let mut ret = Vec::new();
-    for expr_lit in punctuated.iter() {
-        match expr_lit {
-            ExprLit {
-                lit: Lit::Str(lit), ..
-            } => {
-                let path = lit.value();
-                ret.push(path);
-            }
-            _ => {
-                return Err(MacrosError::DestructuringPunctuatedError(
-                    "Must contain only `ExprLit` types".to_string(),
-                ))
-            }
+        match ret {
+            Ok(contract) => contracts.push(contract),
+            Err(e) => match &mut errors {
+                Some(errors) => errors.combine(e),
+                None => errors = Some(e),
+            },
         }
+        paths.push(path);
     }
 
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let mut ret = TokenStream::new();
+    // alias traits are named after the first contract, so invocations in the same module don't clash
+    if let (Some(_), Some((contract_name, ..))) = (&input.accounts, contracts.first()) {
+        compose_alias_traits_tt(contract_name, &accounts).to_tokens(&mut ret);
+    }
+    for (contract_name, types, blueprints) in contracts {
+        types.to_tokens(&mut ret);
+        compose_helper_trait_tt(&contract_name, blueprints).to_tokens(&mut ret);
+    }
+    compose_rebuild_tracking_tt(&paths).to_tokens(&mut ret);
     Ok(ret)
 }
 
-fn compose_helper_trait_tt(schemas: &[ContractSchema]) -> TokenStream {
+/// Resolves the path relative to the manifest dir of the crate which invokes the macro.
+fn resolve_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_absolute() {
+        return path.to_owned();
+    }
+
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(path)
+}
+
+/// References schemas with `include_bytes!`, so cargo recompiles the crate when they change.
+/// Bytes are used instead of a string, because wasm files aren't UTF-8.
+fn compose_rebuild_tracking_tt(paths: &[PathBuf]) -> TokenStream {
+    let paths = paths.iter().map(|path| path.to_string_lossy().into_owned());
+    quote! {
+        #(const _: &[u8] = include_bytes!(#paths);)*
+    }
+}
+
+/// Generates `StateBuilder::with_<alias>`, `State::<alias>` and `State::<alias>_id` methods
+/// like the ones which exist for predefined accounts, e.g. in `NftTokenBuilderAliases`
+/// and `NftTokenAliases` traits for `nft_token`.
+fn compose_alias_traits_tt(contract_name: &str, accounts: &[&str]) -> TokenStream {
+    let prefix = crate::to_pascal_case(contract_name);
+    let builder_trait_ident = format_ident!("{prefix}BuilderAliases");
+    let state_trait_ident = format_ident!("{prefix}Aliases");
+    let with_alias = accounts
+        .iter()
+        .map(|alias| format_ident!("with_{alias}"))
+        .collect::<Vec<_>>();
+    let alias = accounts
+        .iter()
+        .map(|alias| format_ident!("{alias}"))
+        .collect::<Vec<_>>();
+    let alias_id = accounts
+        .iter()
+        .map(|alias| format_ident!("{alias}_id"))
+        .collect::<Vec<_>>();
+
+    quote! {
+        pub trait #builder_trait_ident: Sized {
+            #(
+                fn #with_alias(self, balance: impl lemotests::Nearable) -> Result<Self, lemotests::HelperError>;
+            )*
+        }
+
+        impl<F, T> #builder_trait_ident for lemotests::StateBuilder<F>
+        where
+            F: std::future::Future<Output = lemotests::anyhow::Result<lemotests::workspaces::Worker<T>>>,
+            T: lemotests::workspaces::DevNetwork + std::fmt::Debug,
+        {
+            #(
+                fn #with_alias(self, balance: impl lemotests::Nearable) -> Result<Self, lemotests::HelperError> {
+                    self.with_account(#accounts, balance)
+                }
+            )*
+        }
+
+        pub trait #state_trait_ident {
+            #(
+                fn #alias(&self) -> Result<&lemotests::workspaces::Account, lemotests::HelperError>;
+                fn #alias_id(&self) -> Result<String, lemotests::HelperError>;
+            )*
+        }
+
+        impl<T> #state_trait_ident for lemotests::State<T>
+        where
+            T: lemotests::workspaces::DevNetwork + std::fmt::Debug,
+        {
+            #(
+                fn #alias(&self) -> Result<&lemotests::workspaces::Account, lemotests::HelperError> {
+                    self.account(#accounts)
+                }
+
+                fn #alias_id(&self) -> Result<String, lemotests::HelperError> {
+                    self.account(#accounts).map(|account| account.id().as_str().to_owned())
+                }
+            )*
+        }
+    }
+}
+
+/// Generates `<ContractName>Helpers` trait with methods of the contract, e.g. `NftTokenHelpers`.
+fn compose_helper_trait_tt(contract_name: &str, blueprints: Vec<FunctionBlueprint>) -> TokenStream {
+    let trait_ident = format_ident!("{}", crate::helper_trait_name(contract_name));
     let mut all_declarations_tt = TokenStream::new();
     let mut all_implementations_tt = TokenStream::new();
 
-    for schema in schemas {
-        let (declarations_tt, implementations_tt) =
-            compose_methods_for_accounts_tt(schema, &ACCOUNTS);
+    for blueprint in blueprints {
+        let (declarations_tt, implementations_tt) = compose_method_for_accounts_tt(blueprint);
         declarations_tt.to_tokens(&mut all_declarations_tt);
         implementations_tt.to_tokens(&mut all_implementations_tt);
     }
 
     quote! {
-        pub trait Helper<T>
+        pub trait #trait_ident<T>
         {
            #all_declarations_tt
         }
 
-        impl<T: std::fmt::Debug> Helper<T> for lemotests::State<T>
+        impl<T: std::fmt::Debug> #trait_ident<T> for lemotests::State<T>
         where
             T: lemotests::workspaces::DevNetwork,
         {
@@ -75,99 +182,130 @@ fn compose_helper_trait_tt(schemas: &[ContractSchema]) -> TokenStream {
     }
 }
 
-fn compose_methods_for_accounts_tt<'a>(
-    schema: &'a ContractSchema,
-    accounts: &'a [&'a str],
-) -> (TokenStream, TokenStream) {
-    let mut declarations_tt = TokenStream::new();
-    let mut implementations_tt = TokenStream::new();
-    for blueprint in schema.blueprints(accounts) {
-        let (temp_declarations_tt, temp_implementations_tt) =
-            compose_method_for_accounts_tt(blueprint);
-
-        temp_declarations_tt.to_tokens(&mut declarations_tt);
-        temp_implementations_tt.to_tokens(&mut implementations_tt);
-    }
-
-    (declarations_tt, implementations_tt)
-}
-
 fn compose_method_for_accounts_tt(blueprint: FunctionBlueprint) -> (TokenStream, TokenStream) {
     let mut declarations_tt = TokenStream::new();
     let mut implementations_tt = TokenStream::new();
     let fn_args_tt = blueprint.args_tt();
 
     let method_name_ident = format_ident!("{}", blueprint.trait_method_name);
+    let doc = &blueprint.doc;
+    let (return_type_tt, wrap_tt) = blueprint.return_type_tt();
     let declaration_tt = quote! {
-       fn #method_name_ident(self, #fn_args_tt) -> Result<lemotests::TxWrapper<T>, lemotests::HelperError>;
+       #(#[doc = #doc])*
+       fn #method_name_ident(self, #fn_args_tt) -> Result<#return_type_tt, lemotests::HelperError>;
     };
     declaration_tt.to_tokens(&mut declarations_tt);
 
     let contract_function_name = &blueprint.contract_function_name;
     let account = blueprint.account.clone();
     let contract_name = &blueprint.contract_name;
-    let args_without_types = blueprint.args_without_types();
+    let serialized_args_tt = blueprint.serialized_args_tt();
+    let non_payable_tt = blueprint.non_payable_tt();
 
     let implementation_tt = match blueprint.tx_kind() {
         TxKind::AccountCallContract => {
             let account = account.unwrap();
             quote! {
-                fn #method_name_ident(self, #fn_args_tt) -> Result<lemotests::TxWrapper<T>, lemotests::HelperError> {
-                    let account = self.account_key(#account).cloned();
-                    let contract = self.contract_key(#contract_name).cloned();
+                fn #method_name_ident(self, #fn_args_tt) -> Result<#return_type_tt, lemotests::HelperError> {
+                    let __lemotests_account = self.account_key(#account).cloned();
+                    let __lemotests_contract = self.contract_key(#contract_name).cloned();
 
-                    if account.is_none() && contract.is_none() {
+                    if __lemotests_account.is_none() && __lemotests_contract.is_none() {
                         return Err(lemotests::HelperError::AccountAndContractNotFound(format!("{}, {}", #account, #contract_name)));
                     };
 
-                    let mut json_args = lemotests::serde_json::Map::new();
-                    #(
-                        let value = lemotests::serde_json::to_value(#args_without_types).expect("Fail to serialize argument to `Value`");
-                        json_args.insert(stringify!(#args_without_types).into(), value);
-                    )*
-                    let tx = lemotests::TxWrapper::new(account, contract, #contract_function_name.to_owned(), json_args, lemotests::TxKind::AccountCallContract, self);
-                    Ok(tx)
+                    #serialized_args_tt
+                    let __lemotests_tx = lemotests::TxWrapper::new(__lemotests_account, __lemotests_contract, #contract_function_name.to_owned(), __lemotests_arguments, lemotests::TxKind::AccountCallContract, self);
+                    #non_payable_tt
+                    Ok(#wrap_tt)
                 }
             }
         }
         TxKind::View => quote! {
-            fn #method_name_ident(self, #fn_args_tt) -> Result<lemotests::TxWrapper<T>, lemotests::HelperError> {
-                let contract = self.contract_key(#contract_name).cloned();
+            fn #method_name_ident(self, #fn_args_tt) -> Result<#return_type_tt, lemotests::HelperError> {
+                let __lemotests_contract = self.contract_key(#contract_name).cloned();
 
-                if contract.is_none() {
+                if __lemotests_contract.is_none() {
                     return Err(lemotests::HelperError::ContractNotFound(format!("{}", #contract_name)));
                 };
 
-                let mut json_args = lemotests::serde_json::Map::new();
-                #(
-                    let value = lemotests::serde_json::to_value(#args_without_types).expect("Fail to serialize argument to `Value`");
-                    json_args.insert(stringify!(#args_without_types).into(), value);
-                )*
-                let tx = lemotests::TxWrapper::new(None, contract, #contract_function_name.to_owned(), json_args, lemotests::TxKind::View, self);
-                Ok(tx)
+                #serialized_args_tt
+                let __lemotests_tx = lemotests::TxWrapper::new(None, __lemotests_contract, #contract_function_name.to_owned(), __lemotests_arguments, lemotests::TxKind::View, self);
+                Ok(#wrap_tt)
             }
         },
         TxKind::SelfContractCall => quote! {
-            fn #method_name_ident(self, #fn_args_tt) -> Result<lemotests::TxWrapper<T>, lemotests::HelperError> {
-                let contract = self.contract_key(#contract_name).cloned();
+            fn #method_name_ident(self, #fn_args_tt) -> Result<#return_type_tt, lemotests::HelperError> {
+                let __lemotests_contract = self.contract_key(#contract_name).cloned();
 
-                if contract.is_none() {
+                if __lemotests_contract.is_none() {
                     return Err(lemotests::HelperError::ContractNotFound(format!("{}", #contract_name)));
                 };
 
-                let mut json_args = lemotests::serde_json::Map::new();
-                #(
-                    let value = lemotests::serde_json::to_value(#args_without_types).expect("Fail to serialize argument to `Value`");
-                    json_args.insert(stringify!(#args_without_types).into(), value);
-                )*
-                let tx = lemotests::TxWrapper::new(None, contract, #contract_function_name.to_owned(), json_args, lemotests::TxKind::SelfContractCall, self);
-                Ok(tx)
+                #serialized_args_tt
+                let __lemotests_tx = lemotests::TxWrapper::new(None, __lemotests_contract, #contract_function_name.to_owned(), __lemotests_arguments, lemotests::TxKind::SelfContractCall, self);
+                #non_payable_tt
+                Ok(#wrap_tt)
             }
         },
-        TxKind::ViewAccount => unreachable!(),
+        TxKind::ViewAccount | TxKind::ViewState | TxKind::Patch | TxKind::WaitBlocks => {
+            unreachable!()
+        }
     };
 
     implementation_tt.to_tokens(&mut implementations_tt);
 
     (declarations_tt, implementations_tt)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContractSchema;
+
+    #[test]
+    fn arguments_are_not_shadowed_by_locals() {
+        let schema: ContractSchema = serde_json::from_str(
+            r#"{
+                "name": "market",
+                "functions": [{
+                    "name": "transfer",
+                    "kind": "call",
+                    "arguments": [{ "name": "account", "type": "String" }, { "name": "arguments", "type": "u64" }]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        for blueprint in schema.blueprints(&["alice"]).unwrap() {
+            let (_, implementation) = compose_method_for_accounts_tt(blueprint);
+            let implementation = implementation.to_string();
+            assert!(implementation.contains("to_value (account)"));
+            assert!(implementation.contains("to_value (arguments)"));
+            assert!(!implementation.contains("let account"));
+            assert!(!implementation.contains("let arguments"));
+        }
+    }
+
+    #[test]
+    fn deposits_are_rejected_by_non_payable_functions() {
+        let schema: ContractSchema = serde_json::from_str(
+            r#"{
+                "name": "market",
+                "functions": [
+                    { "name": "buy", "kind": "call", "arguments": [], "payable": true },
+                    { "name": "remove", "kind": "call", "arguments": [], "payable": false },
+                    { "name": "update", "kind": "call", "arguments": [] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        for blueprint in schema.blueprints(&["alice"]).unwrap() {
+            let function = blueprint.contract_function_name.clone();
+            let (_, implementation) = compose_method_for_accounts_tt(blueprint);
+            let non_payable = implementation.to_string().contains(". non_payable ()");
+            assert_eq!(non_payable, function == "remove", "{function}");
+        }
+    }
+}
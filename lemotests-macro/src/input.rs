@@ -0,0 +1,133 @@
+use crate::{MacrosError, SchemaFormat};
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, ExprLit, Ident, Lit, LitStr, Token};
+
+/// Arguments of `add_helpers!`.
+///
+/// Both the list of paths `add_helpers!("nft.json", "market.json")` and named arguments
+/// `add_helpers!(schemas = ["nft.json"], abi = "market_abi.json", wasm = "ft.wasm", accounts = ["seller"])`
+/// are supported. Values of named arguments are a literal or a list of literals.
+pub(crate) struct HelpersInput {
+    pub(crate) schemas: Vec<SchemaSource>,
+    /// Aliases of accounts, predefined accounts (alice, bob, etc.) are used if they aren't provided.
+    pub(crate) accounts: Option<Vec<LitStr>>,
+}
+
+impl Parse for HelpersInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if !(input.peek(Ident) && input.peek2(Token![=])) {
+            let punctuated =
+                Punctuated::<ExprLit, Token![,]>::parse_terminated(input).map_err(|e| {
+                    syn::Error::new(
+                        e.span(),
+                        "add_helpers! can only be used with list of literal paths",
+                    )
+                })?;
+
+            return Ok(Self {
+                schemas: SchemaSource::all(get_literals(punctuated)?, SchemaFormat::Auto),
+                accounts: None,
+            });
+        }
+
+        let mut schemas = None;
+        let mut abi = None;
+        let mut wasm = None;
+        let mut source = None;
+        let mut accounts = None;
+        while !input.is_empty() {
+            let key = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            let values = parse_values(input)?;
+
+            let field = match key.to_string().as_str() {
+                "schemas" => &mut schemas,
+                "abi" => &mut abi,
+                "wasm" => &mut wasm,
+                "source" => &mut source,
+                "accounts" => &mut accounts,
+                _ => return Err(syn::Error::new(
+                    key.span(),
+                    "unknown argument, expected `schemas`, `abi`, `wasm`, `source` or `accounts`",
+                )),
+            };
+            if field.replace(values).is_some() {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "the argument is provided twice",
+                ));
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        if schemas.is_none() && abi.is_none() && wasm.is_none() && source.is_none() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "one of `schemas`, `abi`, `wasm` or `source` arguments is required",
+            ));
+        }
+
+        let mut sources = SchemaSource::all(schemas.unwrap_or_default(), SchemaFormat::Auto);
+        sources.extend(SchemaSource::all(
+            abi.unwrap_or_default(),
+            SchemaFormat::Abi,
+        ));
+        sources.extend(SchemaSource::all(
+            wasm.unwrap_or_default(),
+            SchemaFormat::Wasm,
+        ));
+        sources.extend(SchemaSource::all(
+            source.unwrap_or_default(),
+            SchemaFormat::Source,
+        ));
+        Ok(Self {
+            schemas: sources,
+            accounts,
+        })
+    }
+}
+
+/// The path to the schema and how it should be read.
+pub(crate) struct SchemaSource {
+    pub(crate) lit: LitStr,
+    pub(crate) format: SchemaFormat,
+}
+
+impl SchemaSource {
+    fn all(lits: Vec<LitStr>, format: SchemaFormat) -> Vec<Self> {
+        lits.into_iter().map(|lit| Self { lit, format }).collect()
+    }
+}
+
+/// Parses `"value"` or `["first", "second"]`.
+fn parse_values(input: ParseStream) -> syn::Result<Vec<LitStr>> {
+    if input.peek(LitStr) {
+        return Ok(vec![input.parse()?]);
+    }
+
+    let content;
+    bracketed!(content in input);
+    Ok(Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+fn get_literals<P>(punctuated: Punctuated<ExprLit, P>) -> Result<Vec<LitStr>, syn::Error> {
+    punctuated
+        .into_iter()
+        .map(|expr_lit| match expr_lit.lit {
+            Lit::Str(lit) => Ok(lit),
+            lit => Err(syn::Error::new(
+                lit.span(),
+                MacrosError::DestructuringPunctuatedError(
+                    "Must contain only string literals".to_string(),
+                ),
+            )),
+        })
+        .collect()
+}
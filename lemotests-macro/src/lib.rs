@@ -1,12 +1,21 @@
+mod abi;
 mod blueprint;
 mod errors;
 mod handler;
+mod input;
 mod schema;
+mod source;
+mod types;
+mod validation;
 
+use abi::*;
 use errors::*;
 use handler::*;
+use input::*;
 use proc_macro::TokenStream;
 use schema::*;
+use source::*;
+use validation::*;
 
 /// The macro used to generate the helper methods for the `State<T>` struct from `lemotests` crate.
 ///
@@ -24,6 +33,7 @@ use schema::*;
 ///       "name": "function_name",
 ///       "initable": false,
 ///       "kind": "call",
+///       "serializer": "json",
 ///       "arguments": [
 ///         {
 ///           "name": "argument_one",
@@ -47,7 +57,73 @@ use schema::*;
 /// // you can also use add_helpers!("contract_scheme.json", "contract_scheme2.json");
 /// ```
 ///
-/// It generates methods like this:
+/// Methods are generated for predefined accounts, other aliases can be provided with named arguments.
+/// `StateBuilder::with_<alias>`, `State::<alias>` and `State::<alias>_id` are generated for them
+/// in `<Contract>BuilderAliases` and `<Contract>Aliases` traits named after the first contract:
+///
+/// ```no_run
+/// use lemotests_macro::add_helpers;
+///
+/// add_helpers!(schemas = ["contract_scheme.json"], accounts = ["seller", "buyer"]);
+/// ```
+///
+/// Paths are relative to the `Cargo.toml` of the crate which invokes the macro.
+/// The crate is recompiled when schemas are changed.
+///
+/// `serializer` is optional and can be `json` (default) or `borsh`.
+/// `returns` is the optional type of the result, helpers of such functions return `lemotests::TypedTx`
+/// which decodes the result with JSON or with borsh if `result_serializer` is `borsh`.
+/// Optional `doc`, `initable`, `private` and `payable` fields are added to the documentation
+/// of generated methods. Only `call_<contract>_<function>` is generated for private and init functions.
+/// Helpers of functions with `"payable": false` fail before execution if a deposit is attached,
+/// deposits aren't checked if `payable` is omitted.
+///
+/// Structs and enums from the optional `types` section are generated next to helpers
+/// with serde derives and can be used in arguments and results:
+///
+/// ```json
+/// "types": [
+///   { "kind": "struct", "name": "Sale", "fields": [{ "name": "price", "type": "YoctoNear" }] },
+///   { "kind": "enum", "name": "Status", "variants": [{ "name": "Listed" }, { "name": "Sold", "type": "Sale" }] }
+/// ]
+/// ```
+///
+/// The ABI generated by near-sdk is accepted as well, it's detected by content or passed
+/// with `abi` argument. Types of parameters are mapped from JSON schemas, e.g. `U128` into `YoctoNear`,
+/// structs and enums from definitions are generated, other types become `serde_json::Value`:
+///
+/// ```no_run
+/// use lemotests_macro::add_helpers;
+///
+/// add_helpers!(abi = "target/near/contract_name_abi.json");
+/// ```
+///
+/// The ABI embedded into the compiled contract is read with `wasm` argument:
+///
+/// ```no_run
+/// use lemotests_macro::add_helpers;
+///
+/// add_helpers!(wasm = "target/near/contract_name.wasm");
+/// ```
+///
+/// `source` argument takes the Rust source of the contract and parses its `#[near_bindgen]` impl blocks:
+/// `&mut self` methods become calls, `&self` methods become views,
+/// `#[init]`, `#[payable]`, `#[private]` and `#[serializer(borsh)]` are taken into account.
+///
+/// ```no_run
+/// use lemotests_macro::add_helpers;
+///
+/// add_helpers!(source = "../contracts/contract_name/src/lib.rs");
+/// ```
+///
+/// Schemas are validated before generating methods: names of contracts and functions must contain
+/// only letters, digits and underscores, names of functions and arguments must be unique.
+/// Arguments named with Rust keywords are generated as raw identifiers, e.g. `r#type`.
+/// Names starting with `__lemotests_` are reserved for locals of generated methods.
+///
+/// Methods of every contract are generated in a separate trait named after the contract,
+/// e.g. `ContractNameHelpers` for `contract_name`, so the macro can be invoked several times
+/// and traits can be imported selectively. It generates methods like this:
 ///```no_run
 /// use lemotests::prelude::*;
 ///
@@ -63,5 +139,7 @@ use schema::*;
 /// ```
 #[proc_macro]
 pub fn add_helpers(item: TokenStream) -> TokenStream {
-    handle_input_tt(item).unwrap().into()
+    handle_input_tt(item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
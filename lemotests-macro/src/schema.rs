@@ -1,51 +1,88 @@
 use crate::blueprint::FunctionBlueprint;
 use crate::MacrosError;
+use lemotests::abi::AbiRoot;
 use lemotests::TxKind;
+use quote::ToTokens;
 use serde::Deserialize;
-use std::fs::File;
-use std::io::{BufReader, Read};
 use std::path::Path;
-use syn::FnArg;
-
-pub(crate) fn read_json_schemas_from_file<P>(
-    paths: &[P],
-) -> Result<Vec<BufReader<File>>, MacrosError>
-where
-    P: AsRef<Path>,
-{
-    let mut ret = Vec::new();
-    for path in paths {
-        let file = File::open(path)?;
-        ret.push(BufReader::new(file))
-    }
+use syn::{parse_quote, FnArg, Ident, Type};
 
-    Ok(ret)
+/// Format of the file passed to `add_helpers!`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SchemaFormat {
+    /// The `lemotests` schema or the ABI generated by near-sdk, detected by content.
+    Auto,
+    /// The ABI generated by near-sdk.
+    Abi,
+    /// The compiled contract with the embedded ABI.
+    Wasm,
+    /// Rust source of the contract with `#[near_bindgen]` impl blocks.
+    Source,
 }
 
-pub(crate) fn deserialize_json_schemas<R: Read>(
-    readers: &mut [BufReader<R>],
-) -> Result<Vec<ContractSchema>, MacrosError> {
-    let mut ret = Vec::new();
-    for reader in readers {
-        let schema =
-            serde_json::from_reader(reader).map_err(MacrosError::DeserializeJsonSchemaError)?;
-        ret.push(schema)
+pub(crate) fn read_json_schema(
+    path: &Path,
+    format: SchemaFormat,
+) -> Result<ContractSchema, MacrosError> {
+    if format == SchemaFormat::Wasm {
+        let abi =
+            lemotests::abi::from_wasm(path).map_err(|source| MacrosError::ExtractAbiError {
+                path: path.to_owned(),
+                source,
+            })?;
+        return crate::contract_schema_from_abi(&abi, path);
+    }
+
+    let content =
+        std::fs::read_to_string(path).map_err(|source| MacrosError::FailedToOpenFileError {
+            path: path.to_owned(),
+            source,
+        })?;
+    if format == SchemaFormat::Source {
+        return crate::contract_schema_from_source(path, &content);
+    }
+
+    let deserialize_error = |source| MacrosError::DeserializeJsonSchemaError {
+        path: path.to_owned(),
+        source,
+    };
+
+    let is_abi = match format {
+        SchemaFormat::Abi | SchemaFormat::Wasm | SchemaFormat::Source => true,
+        SchemaFormat::Auto => {
+            let value = serde_json::from_str(&content).map_err(deserialize_error)?;
+            AbiRoot::is_abi(&value)
+        }
+    };
+
+    if is_abi {
+        let abi = serde_json::from_str(&content).map_err(deserialize_error)?;
+        crate::contract_schema_from_abi(&abi, path)
+    } else {
+        serde_json::from_str(&content).map_err(deserialize_error)
     }
-    Ok(ret)
 }
 
 #[derive(Deserialize)]
 pub(crate) struct ContractSchema {
     pub(crate) name: String,
     pub(crate) functions: Vec<FunctionSchema>,
+    /// Types which are generated next to helpers and can be used in arguments and results.
+    #[serde(default)]
+    pub(crate) types: Vec<TypeSchema>,
 }
 
 impl ContractSchema {
-    pub(crate) fn blueprints(&self, accounts: &[&str]) -> Vec<FunctionBlueprint> {
-        self.functions
-            .iter()
-            .flat_map(|f| f.blueprints(self.name.clone(), accounts))
-            .collect()
+    pub(crate) fn blueprints(
+        &self,
+        accounts: &[&str],
+    ) -> Result<Vec<FunctionBlueprint>, MacrosError> {
+        let mut ret = Vec::new();
+        for function in &self.functions {
+            ret.extend(function.blueprints(self.name.clone(), accounts)?);
+        }
+
+        Ok(ret)
     }
 }
 
@@ -56,11 +93,39 @@ pub enum FunctionKind {
     View,
 }
 
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Serializer {
+    #[default]
+    Json,
+    Borsh,
+}
+
 #[derive(Deserialize)]
 pub(crate) struct FunctionSchema {
     pub(crate) name: String,
-    kind: FunctionKind,
+    pub(crate) kind: FunctionKind,
+    #[serde(default)]
+    pub(crate) serializer: Serializer,
     pub(crate) arguments: Vec<ArgumentSchema>,
+    /// Documentation of the function, added to generated methods.
+    #[serde(default)]
+    pub(crate) doc: Option<String>,
+    /// The function initializes the contract, so only `call_<contract>_<function>` is generated.
+    #[serde(default)]
+    pub(crate) initable: bool,
+    /// The function can be called only by the contract itself, so account methods aren't generated.
+    #[serde(default)]
+    pub(crate) private: bool,
+    /// The function accepts a deposit. Deposits aren't checked if it's unknown,
+    /// helpers of functions which aren't payable fail before execution if the deposit is attached.
+    #[serde(default)]
+    pub(crate) payable: Option<bool>,
+    /// The type of the result, helpers of such functions return `lemotests::TypedTx`.
+    #[serde(default)]
+    pub(crate) returns: Option<String>,
+    #[serde(default)]
+    pub(crate) result_serializer: Serializer,
 }
 
 impl FunctionSchema {
@@ -68,8 +133,16 @@ impl FunctionSchema {
         &self,
         contract_name: String,
         accounts: &[&str],
-    ) -> Vec<FunctionBlueprint> {
-        let arguments = self.arguments();
+    ) -> Result<Vec<FunctionBlueprint>, MacrosError> {
+        let arguments = self.arguments()?;
+        let returns = self.returns()?;
+        let doc = self.doc();
+        let typed = |blueprint: FunctionBlueprint| {
+            blueprint
+                .with_doc(doc.clone())
+                .with_returns(returns.clone(), self.result_serializer)
+                .with_payable(self.payable)
+        };
         let mut ret = Vec::new();
         let contract_function_name = &self.name;
         match self.kind {
@@ -81,8 +154,14 @@ impl FunctionSchema {
                     None,
                     TxKind::SelfContractCall,
                     contract_name.clone(),
+                    self.serializer,
                 );
-                ret.push(self_contract_call);
+                ret.push(typed(self_contract_call));
+
+                // init functions are called by the contract after the deploy, like private ones
+                if self.private || self.initable {
+                    return Ok(ret);
+                }
 
                 for account in accounts {
                     let blueprint = FunctionBlueprint::new(
@@ -92,10 +171,11 @@ impl FunctionSchema {
                         Some(account.to_string()),
                         TxKind::AccountCallContract,
                         contract_name.clone(),
+                        self.serializer,
                     );
-                    ret.push(blueprint);
+                    ret.push(typed(blueprint));
                 }
-                ret
+                Ok(ret)
             }
             FunctionKind::View => {
                 let blueprint = FunctionBlueprint::new(
@@ -105,29 +185,166 @@ impl FunctionSchema {
                     None,
                     TxKind::View,
                     contract_name,
+                    self.serializer,
                 );
-                vec![blueprint]
+                Ok(vec![typed(blueprint)])
             }
         }
     }
 
-    fn arguments(&self) -> Vec<FnArg> {
+    /// Lines of documentation for generated methods: the doc of the function and its modifiers.
+    fn doc(&self) -> Vec<String> {
+        let mut ret = self
+            .doc
+            .iter()
+            .flat_map(|doc| doc.lines())
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+
+        let modifiers = [
+            (self.initable, "Initializes the contract."),
+            (
+                self.private,
+                "Private: can be called only by the contract itself.",
+            ),
+            (
+                self.payable == Some(true),
+                "Payable: a deposit can be attached with `with_deposit`.",
+            ),
+            (
+                self.payable == Some(false),
+                "Not payable: the chain fails if a deposit is attached.",
+            ),
+        ];
+        for (_, line) in modifiers.iter().filter(|(enabled, _)| *enabled) {
+            if !ret.is_empty() {
+                ret.push(String::new());
+            }
+            ret.push(format!(" {line}"));
+        }
+
+        ret
+    }
+
+    fn returns(&self) -> Result<Option<Type>, MacrosError> {
+        self.returns
+            .as_deref()
+            .map(|ty| {
+                syn::parse_str(ty).map_err(|source| MacrosError::InvalidReturnTypeError {
+                    function: self.name.clone(),
+                    ty: ty.to_owned(),
+                    source,
+                })
+            })
+            .transpose()
+    }
+
+    fn arguments(&self) -> Result<Vec<FnArg>, MacrosError> {
         self.arguments
             .iter()
-            .map(|arg| syn::parse_str(&arg.argument()).unwrap())
+            .map(|arg| {
+                arg.argument()
+                    .map_err(|source| MacrosError::InvalidArgumentError {
+                        function: self.name.clone(),
+                        argument: arg.name.clone(),
+                        ty: arg.r#type.clone(),
+                        source,
+                    })
+            })
             .collect()
     }
 }
 
-#[derive(Deserialize)]
+/// The struct or enum which is generated with serde derives.
+/// Types with the same name and shape can be declared by several contracts, they are generated once.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum TypeSchema {
+    Struct {
+        name: String,
+        fields: Vec<ArgumentSchema>,
+    },
+    Enum {
+        name: String,
+        variants: Vec<VariantSchema>,
+    },
+}
+
+impl TypeSchema {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            TypeSchema::Struct { name, .. } | TypeSchema::Enum { name, .. } => name,
+        }
+    }
+}
+
+/// The variant of the enum, it's a unit variant if neither `fields` nor `type` are provided.
+#[derive(Deserialize, Clone, PartialEq)]
+pub(crate) struct VariantSchema {
+    pub(crate) name: String,
+    /// Fields of the struct variant, like `Sold { price: Near }`.
+    #[serde(default)]
+    pub(crate) fields: Option<Vec<ArgumentSchema>>,
+    /// The type of the newtype variant, like `Sold(Near)`.
+    #[serde(default)]
+    pub(crate) r#type: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
 pub(crate) struct ArgumentSchema {
     pub(crate) name: String,
     pub(crate) r#type: String,
 }
 
 impl ArgumentSchema {
-    pub(crate) fn argument(&self) -> String {
-        let r#type = self.r#type.replacen("String", "&str", 1);
-        format!("{}: {}", self.name, r#type)
+    /// The argument of the generated method. `String` and `Option<String>` are taken as `&str`
+    /// and `Option<&str>`, other types, like `StringPair` or `Vec<String>`, are kept as is.
+    pub(crate) fn argument(&self) -> syn::Result<FnArg> {
+        let ident = syn::parse_str::<Ident>(&crate::escape_ident(&self.name))?;
+        let ty = syn::parse_str::<Type>(&self.r#type)?;
+        let ty: Type = match ty.to_token_stream().to_string().as_str() {
+            "String" => parse_quote!(&str),
+            "Option < String >" => parse_quote!(Option<&str>),
+            _ => ty,
+        };
+        Ok(parse_quote!(#ident: #ty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argument(ty: &str) -> String {
+        let argument = ArgumentSchema {
+            name: "type".to_owned(),
+            r#type: ty.to_owned(),
+        };
+        argument.argument().unwrap().to_token_stream().to_string()
+    }
+
+    #[test]
+    fn init_functions_are_called_by_the_contract() {
+        let schema: ContractSchema = serde_json::from_str(
+            r#"{ "name": "nft", "functions": [{ "name": "new", "kind": "call", "arguments": [], "initable": true }] }"#,
+        )
+        .unwrap();
+
+        let names = schema
+            .blueprints(&["alice", "bob"])
+            .unwrap()
+            .into_iter()
+            .map(|blueprint| blueprint.trait_method_name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["call_nft_new"]);
+    }
+
+    #[test]
+    fn only_strings_are_borrowed() {
+        assert_eq!(argument("String"), "r#type : & str");
+        assert_eq!(argument("Option<String>"), "r#type : Option < & str >");
+        assert_eq!(argument("StringPair"), "r#type : StringPair");
+        assert_eq!(argument("Vec<String>"), "r#type : Vec < String >");
+        assert_eq!(argument("(String, u8)"), "r#type : (String , u8)");
     }
 }
@@ -0,0 +1,417 @@
+use crate::{
+    ArgumentSchema, ContractSchema, FunctionKind, FunctionSchema, MacrosError, Serializer,
+};
+use quote::ToTokens;
+use std::path::Path;
+use syn::ext::IdentExt;
+use syn::{
+    Attribute, FnArg, GenericArgument, ImplItem, ImplItemMethod, Item, ItemImpl, Lit, Meta, Pat,
+    PathArguments, ReturnType, Type, Visibility,
+};
+
+/// Parses `#[near_bindgen]` impl blocks of the contract source into the schema.
+/// Only the file itself and its inline modules are parsed, `mod foo;` declarations aren't followed.
+pub(crate) fn contract_schema_from_source(
+    path: &Path,
+    content: &str,
+) -> Result<ContractSchema, MacrosError> {
+    let file = syn::parse_file(content).map_err(|source| MacrosError::ParseSourceError {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    let mut problems = Vec::new();
+    let mut functions = Vec::new();
+    collect_functions(&file.items, &mut functions, &mut problems);
+    if !problems.is_empty() {
+        return Err(MacrosError::InvalidSchemaError(problems.join("; ")));
+    }
+    if functions.is_empty() {
+        return Err(MacrosError::InvalidSchemaError(format!(
+            "`{}` doesn't contain `#[near_bindgen]` impl blocks with public methods",
+            path.display()
+        )));
+    }
+
+    Ok(ContractSchema {
+        name: contract_name(path),
+        functions,
+        types: Vec::new(),
+    })
+}
+
+/// Names the contract after the crate for `src/lib.rs` and after the file otherwise.
+fn contract_name(path: &Path) -> String {
+    let mut names = path
+        .iter()
+        .rev()
+        .map(|part| part.to_string_lossy())
+        .map(|part| part.trim_end_matches(".rs").to_owned());
+
+    names
+        .find(|name| !matches!(name.as_str(), "lib" | "main" | "mod" | "src"))
+        .unwrap_or_default()
+        .replace('-', "_")
+}
+
+fn collect_functions(
+    items: &[Item],
+    functions: &mut Vec<FunctionSchema>,
+    problems: &mut Vec<String>,
+) {
+    for item in items {
+        match item {
+            Item::Impl(item_impl) if has_attr(&item_impl.attrs, "near_bindgen") => {
+                for method in exported_methods(item_impl) {
+                    match function_schema(method) {
+                        Ok(function) => functions.push(function),
+                        Err(problem) => problems.push(problem),
+                    }
+                }
+            }
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    collect_functions(items, functions, problems);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Methods of trait impls are exported, methods of inherent impls are exported if they are public.
+fn exported_methods(item_impl: &ItemImpl) -> impl Iterator<Item = &ImplItemMethod> {
+    let is_trait_impl = item_impl.trait_.is_some();
+    item_impl.items.iter().filter_map(move |item| match item {
+        ImplItem::Method(method)
+            if is_trait_impl || matches!(method.vis, Visibility::Public(_)) =>
+        {
+            Some(method)
+        }
+        _ => None,
+    })
+}
+
+fn function_schema(method: &ImplItemMethod) -> Result<FunctionSchema, String> {
+    let name = method.sig.ident.to_string();
+    let initable = has_attr(&method.attrs, "init");
+    let kind = match method.sig.receiver() {
+        Some(FnArg::Receiver(receiver)) if receiver.mutability.is_some() => FunctionKind::Call,
+        Some(_) => FunctionKind::View,
+        None if initable => FunctionKind::Call,
+        None => FunctionKind::View,
+    };
+
+    let mut serializer = serializer_attr(&method.attrs, "serializer").unwrap_or_default();
+    let result_serializer = serializer_attr(&method.attrs, "result_serializer").unwrap_or_default();
+    let mut arguments = Vec::new();
+    for input in &method.sig.inputs {
+        let FnArg::Typed(pat_type) = input else {
+            continue;
+        };
+        // results of promises are passed by the runtime
+        if pat_type
+            .attrs
+            .iter()
+            .any(|attr| attr_name(attr).is_some_and(|name| name.starts_with("callback")))
+        {
+            continue;
+        }
+        if let Some(arg_serializer) = serializer_attr(&pat_type.attrs, "serializer") {
+            serializer = arg_serializer;
+        }
+
+        let Pat::Ident(pat_ident) = &*pat_type.pat else {
+            return Err(format!(
+                "arguments of function `{name}` must be identifiers"
+            ));
+        };
+        arguments.push((pat_ident.ident.unraw().to_string(), &*pat_type.ty));
+    }
+
+    let arguments = arguments
+        .into_iter()
+        .map(|(arg_name, ty)| {
+            let r#type = rust_type(ty, serializer).ok_or_else(|| {
+                format!(
+                    "argument `{arg_name}` of function `{name}`: type `{}` isn't supported with borsh",
+                    ty.to_token_stream()
+                )
+            })?;
+            Ok(ArgumentSchema {
+                name: arg_name,
+                r#type,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(FunctionSchema {
+        name,
+        kind,
+        serializer,
+        arguments,
+        doc: doc(&method.attrs),
+        initable,
+        private: has_attr(&method.attrs, "private"),
+        payable: Some(has_attr(&method.attrs, "payable")),
+        returns: (!initable)
+            .then(|| returns(&method.sig.output, result_serializer))
+            .flatten(),
+        result_serializer,
+    })
+}
+
+/// Maps the result of the method, `PromiseOrValue<T>` is unwrapped into `T`.
+/// Results which can't be expressed, like `Promise`, are left untyped.
+fn returns(output: &ReturnType, serializer: Serializer) -> Option<String> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+
+    let ty = match &**ty {
+        Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            match (segment.ident.to_string().as_str(), &segment.arguments) {
+                ("Promise" | "Self", _) => return None,
+                ("PromiseOrValue", PathArguments::AngleBracketed(args)) => {
+                    match args.args.first() {
+                        Some(GenericArgument::Type(inner)) => inner,
+                        _ => return None,
+                    }
+                }
+                _ => ty,
+            }
+        }
+        _ => ty,
+    };
+
+    rust_type(ty, serializer).filter(|ty| ty != "()")
+}
+
+/// Maps the type of the contract argument into the type of the helper argument.
+/// Types which are unknown outside of the contract become `serde_json::Value` for JSON
+/// and aren't supported for borsh.
+fn rust_type(ty: &Type, serializer: Serializer) -> Option<String> {
+    let unknown = || match serializer {
+        Serializer::Json => Some(crate::VALUE_TYPE.to_owned()),
+        Serializer::Borsh => None,
+    };
+
+    match ty {
+        Type::Reference(reference) => rust_type(&reference.elem, serializer),
+        Type::Paren(paren) => rust_type(&paren.elem, serializer),
+        Type::Group(group) => rust_type(&group.elem, serializer),
+        Type::Tuple(tuple) => {
+            let elems = tuple
+                .elems
+                .iter()
+                .map(|elem| rust_type(elem, serializer))
+                .collect::<Option<Vec<_>>>()?;
+            match elems.as_slice() {
+                [] => Some("()".to_owned()),
+                elems => Some(format!("({},)", elems.join(", "))),
+            }
+        }
+        Type::Array(array) => Some(format!(
+            "[{}; {}]",
+            rust_type(&array.elem, serializer)?,
+            array.len.to_token_stream()
+        )),
+        Type::Slice(slice) => Some(format!("Vec<{}>", rust_type(&slice.elem, serializer)?)),
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let segment = type_path.path.segments.last()?;
+            let ident = segment.ident.to_string();
+            match ident.as_str() {
+                "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128"
+                | "bool" | "f32" | "f64" | "String" => Some(ident),
+                "str" | "AccountId" | "TokenId" => Some("String".to_owned()),
+                "Balance" => Some("u128".to_owned()),
+                // near-sdk encodes these as strings in JSON
+                "U64" if matches!(serializer, Serializer::Json) => Some("String".to_owned()),
+                "U128" if matches!(serializer, Serializer::Json) => {
+                    Some("lemotests::YoctoNear".to_owned())
+                }
+                "Gas" if matches!(serializer, Serializer::Json) => {
+                    Some("lemotests::Gas".to_owned())
+                }
+                "U64" | "Gas" => Some("u64".to_owned()),
+                "U128" => Some("u128".to_owned()),
+                "Option" | "Vec" => {
+                    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                        return unknown();
+                    };
+                    match args.args.first() {
+                        Some(GenericArgument::Type(inner)) => {
+                            Some(format!("{ident}<{}>", rust_type(inner, serializer)?))
+                        }
+                        _ => unknown(),
+                    }
+                }
+                _ => unknown(),
+            }
+        }
+        _ => unknown(),
+    }
+}
+
+/// Serializer from `#[serializer(borsh)]` or `#[result_serializer(borsh)]` attributes.
+fn serializer_attr(attrs: &[Attribute], name: &str) -> Option<Serializer> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident(name))
+        .find_map(|attr| {
+            let tokens = attr.tokens.to_string();
+            if tokens.contains("borsh") {
+                Some(Serializer::Borsh)
+            } else if tokens.contains("json") {
+                Some(Serializer::Json)
+            } else {
+                None
+            }
+        })
+}
+
+fn doc(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(meta)) => match meta.lit {
+                Lit::Str(lit) => Some(lit.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Returns the last segment of the attribute path, so `near_sdk::near_bindgen` is matched too.
+fn attr_name(attr: &Attribute) -> Option<String> {
+    attr.path
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+}
+
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr_name(attr).as_deref() == Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+        use near_sdk::{near_bindgen, AccountId, json_types::U128};
+
+        #[near_bindgen]
+        impl Contract {
+            #[init]
+            pub fn new(owner_id: AccountId) -> Self {
+                todo!()
+            }
+
+            /// Mints the token.
+            #[payable]
+            pub fn nft_mint(&mut self, token_id: TokenId, memo: Option<String>, metadata: TokenMetadata) {}
+
+            pub fn nft_token(&self, token_id: &TokenId) -> Option<Token> {
+                None
+            }
+
+            #[private]
+            pub fn on_transfer(&mut self, #[serializer(borsh)] amounts: Vec<U128>, #[callback_unwrap] ok: bool) {}
+
+            fn internal(&mut self) {}
+        }
+
+        mod core {
+            #[near_bindgen]
+            impl NonFungibleTokenCore for Contract {
+                fn nft_transfer(&mut self, receiver_id: AccountId, amount: U128) {}
+            }
+        }
+
+        impl Contract {
+            pub fn not_exported(&self) {}
+        }
+    "#;
+
+    #[test]
+    fn source_is_parsed() {
+        let schema =
+            contract_schema_from_source(Path::new("contracts/nft-token/src/lib.rs"), SOURCE)
+                .unwrap();
+        assert_eq!(schema.name, "nft_token");
+
+        let names = schema
+            .functions
+            .iter()
+            .map(|function| function.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "new",
+                "nft_mint",
+                "nft_token",
+                "on_transfer",
+                "nft_transfer"
+            ]
+        );
+
+        let [new, mint, token, on_transfer, transfer] = &schema.functions[..] else {
+            unreachable!()
+        };
+        assert!(new.initable && matches!(new.kind, FunctionKind::Call));
+        assert!(mint.payable == Some(true) && matches!(mint.kind, FunctionKind::Call));
+        assert_eq!(mint.doc.as_deref(), Some(" Mints the token."));
+        assert!(matches!(token.kind, FunctionKind::View));
+        assert_eq!(
+            token.returns.as_deref(),
+            Some("Option<lemotests::serde_json::Value>")
+        );
+        assert_eq!(new.returns, None);
+        assert_eq!(mint.returns, None);
+        assert!(on_transfer.private && matches!(on_transfer.serializer, Serializer::Borsh));
+
+        let types = [mint, token, on_transfer, transfer]
+            .iter()
+            .flat_map(|function| &function.arguments)
+            .map(|arg| arg.r#type.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                "String",
+                "Option<String>",
+                crate::VALUE_TYPE,
+                "String",
+                "Vec<u128>",
+                "String",
+                "lemotests::YoctoNear"
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_borsh_types_are_reported() {
+        let source = r#"
+            #[near_bindgen]
+            impl Contract {
+                #[serializer(borsh)]
+                pub fn set(&mut self, metadata: TokenMetadata) {}
+            }
+        "#;
+
+        let error = contract_schema_from_source(Path::new("src/market.rs"), source)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("argument `metadata` of function `set`"));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{ArgumentSchema, MacrosError, TypeSchema, VariantSchema};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, Type};
+
+impl TypeSchema {
+    /// Generates the type with serde derives from `lemotests::serde`,
+    /// so the crate which invokes the macro doesn't need serde dependency.
+    pub(crate) fn type_tt(&self) -> Result<TokenStream, MacrosError> {
+        let ident = format_ident!("{}", self.name());
+        let derive_tt = quote! {
+            #[derive(Debug, Clone, PartialEq, lemotests::serde::Serialize, lemotests::serde::Deserialize)]
+            #[serde(crate = "lemotests::serde")]
+            #[allow(dead_code, non_camel_case_types, non_snake_case)]
+        };
+
+        let ret = match self {
+            TypeSchema::Struct { name, fields } => {
+                let fields = fields_tt(name, fields)?;
+                quote! {
+                    #derive_tt
+                    pub struct #ident {
+                        #(pub #fields,)*
+                    }
+                }
+            }
+            TypeSchema::Enum { name, variants } => {
+                let variants = variants
+                    .iter()
+                    .map(|variant| variant_tt(name, variant))
+                    .collect::<Result<Vec<_>, _>>()?;
+                quote! {
+                    #derive_tt
+                    pub enum #ident {
+                        #(#variants,)*
+                    }
+                }
+            }
+        };
+
+        Ok(ret)
+    }
+}
+
+fn variant_tt(owner: &str, variant: &VariantSchema) -> Result<TokenStream, MacrosError> {
+    let ident = format_ident!("{}", variant.name);
+    let owner = format!("{owner}::{}", variant.name);
+    let ret = match (&variant.fields, &variant.r#type) {
+        (Some(fields), _) => {
+            let fields = fields_tt(&owner, fields)?;
+            quote!(#ident { #(#fields),* })
+        }
+        (None, Some(ty)) => {
+            let ty = parse_type(&owner, "0", ty)?;
+            quote!(#ident(#ty))
+        }
+        (None, None) => quote!(#ident),
+    };
+
+    Ok(ret)
+}
+
+fn fields_tt(owner: &str, fields: &[ArgumentSchema]) -> Result<Vec<TokenStream>, MacrosError> {
+    fields
+        .iter()
+        .map(|field| {
+            let ident = syn::parse_str::<Ident>(&crate::escape_ident(&field.name))
+                .map_err(|source| field_error(owner, &field.name, &field.r#type, source))?;
+            let ty = parse_type(owner, &field.name, &field.r#type)?;
+            Ok(quote!(#ident: #ty))
+        })
+        .collect()
+}
+
+fn parse_type(owner: &str, field: &str, ty: &str) -> Result<Type, MacrosError> {
+    syn::parse_str(ty).map_err(|source| field_error(owner, field, ty, source))
+}
+
+fn field_error(owner: &str, field: &str, ty: &str, source: syn::Error) -> MacrosError {
+    MacrosError::InvalidFieldError {
+        owner: owner.to_owned(),
+        field: field.to_owned(),
+        ty: ty.to_owned(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_tt(json: &str) -> String {
+        let schema: TypeSchema = serde_json::from_str(json).unwrap();
+        schema.type_tt().unwrap().to_string()
+    }
+
+    #[test]
+    fn structs_are_generated() {
+        let ty = type_tt(
+            r#"{
+                "kind": "struct",
+                "name": "Sale",
+                "fields": [{ "name": "price", "type": "Near" }, { "name": "type", "type": "Option<String>" }]
+            }"#,
+        );
+        assert!(ty.contains("# [serde (crate = \"lemotests::serde\")]"));
+        assert!(
+            ty.contains("pub struct Sale { pub price : Near , pub r#type : Option < String > , }")
+        );
+    }
+
+    #[test]
+    fn enums_are_generated() {
+        let ty = type_tt(
+            r#"{
+                "kind": "enum",
+                "name": "Status",
+                "variants": [
+                    { "name": "Draft" },
+                    { "name": "Listed", "type": "Near" },
+                    { "name": "Sold", "fields": [{ "name": "buyer", "type": "String" }] }
+                ]
+            }"#,
+        );
+        assert!(
+            ty.contains("pub enum Status { Draft , Listed (Near) , Sold { buyer : String } , }")
+        );
+    }
+
+    #[test]
+    fn invalid_field_types_are_reported() {
+        let schema: TypeSchema = serde_json::from_str(
+            r#"{ "kind": "struct", "name": "Sale", "fields": [{ "name": "price", "type": "Near<" }] }"#,
+        )
+        .unwrap();
+        let error = schema.type_tt().unwrap_err().to_string();
+        assert!(error.contains("field `price: Near<` of type `Sale`"));
+    }
+}
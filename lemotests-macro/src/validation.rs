@@ -0,0 +1,445 @@
+use crate::blueprint::FunctionBlueprint;
+use crate::{ArgumentSchema, ContractSchema, MacrosError, TypeSchema};
+use std::collections::{HashMap, HashSet};
+use syn::LitStr;
+
+/// Keywords which can be used as identifiers with `r#` prefix.
+const KEYWORDS: [&str; 47] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Keywords which can't be used as identifiers at all.
+const RESERVED_IDENTS: [&str; 5] = ["self", "Self", "super", "crate", "_"];
+
+/// Prefix of locals in generated methods, so they don't shadow arguments.
+const LOCAL_PREFIX: &str = "__lemotests_";
+
+/// Returns the identifier for the name, keywords are escaped with `r#`.
+pub(crate) fn escape_ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_owned()
+    }
+}
+
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && is_ident_part(chars.as_str())
+}
+
+/// Converts `nft_token` into `NftToken`.
+pub(crate) fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Checks names which are parts of generated method names, e.g. `nft` in `alice_call_nft_mint`.
+fn is_ident_part(name: &str) -> bool {
+    name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Checks that aliases of accounts can be used as method names and are unique.
+pub(crate) fn validate_aliases(aliases: &[LitStr]) -> Result<(), syn::Error> {
+    let mut errors: Option<syn::Error> = None;
+    let mut unique = HashSet::new();
+    for alias in aliases {
+        let name = alias.value();
+        let problem = if !is_ident(&name)
+            || KEYWORDS.contains(&name.as_str())
+            || RESERVED_IDENTS.contains(&name.as_str())
+        {
+            Some(format!("account alias `{name}` isn't a valid identifier"))
+        } else if !unique.insert(name.clone()) {
+            Some(format!("account alias `{name}` is declared twice"))
+        } else {
+            None
+        };
+
+        if let Some(problem) = problem {
+            let error = syn::Error::new(alias.span(), problem);
+            match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        }
+    }
+
+    errors.map_or(Ok(()), Err)
+}
+
+impl ContractSchema {
+    /// Checks that the schema generates valid code, all problems are reported at once.
+    pub(crate) fn validate(&self) -> Result<(), MacrosError> {
+        let mut problems = Vec::new();
+        let starts_with_letter = self.name.starts_with(char::is_alphabetic);
+        if !starts_with_letter || !is_ident_part(&self.name) {
+            problems.push(format!(
+                "contract name `{}` must contain only letters, digits and underscores \
+                and start with a letter",
+                self.name
+            ));
+        }
+
+        let mut functions = HashSet::new();
+        for function in &self.functions {
+            if function.name.is_empty() || !is_ident_part(&function.name) {
+                problems.push(format!(
+                    "function name `{}` must contain only letters, digits and underscores",
+                    function.name
+                ));
+            }
+            if !functions.insert(&function.name) {
+                problems.push(format!("function `{}` is declared twice", function.name));
+            }
+
+            let mut arguments = HashSet::new();
+            for argument in &function.arguments {
+                if RESERVED_IDENTS.contains(&argument.name.as_str()) {
+                    problems.push(format!(
+                        "argument `{}` of function `{}` is a reserved keyword",
+                        argument.name, function.name
+                    ));
+                } else if !is_ident(&argument.name) {
+                    problems.push(format!(
+                        "argument `{}` of function `{}` isn't a valid identifier",
+                        argument.name, function.name
+                    ));
+                } else if argument.name.starts_with(LOCAL_PREFIX) {
+                    problems.push(format!(
+                        "argument `{}` of function `{}` starts with `{LOCAL_PREFIX}`, \
+                        which is reserved for locals of generated methods",
+                        argument.name, function.name
+                    ));
+                }
+                if !arguments.insert(&argument.name) {
+                    problems.push(format!(
+                        "argument `{}` of function `{}` is declared twice",
+                        argument.name, function.name
+                    ));
+                }
+            }
+        }
+
+        let mut types = HashSet::new();
+        for ty in &self.types {
+            let name = ty.name();
+            if !is_type_name(name) {
+                problems.push(format!("type name `{name}` isn't a valid identifier"));
+            }
+            if !types.insert(name) {
+                problems.push(format!("type `{name}` is declared twice"));
+            }
+
+            match ty {
+                TypeSchema::Struct { fields, .. } => validate_fields(name, fields, &mut problems),
+                TypeSchema::Enum { variants, .. } => {
+                    let mut unique = HashSet::new();
+                    for variant in variants {
+                        if !is_type_name(&variant.name) {
+                            problems.push(format!(
+                                "variant `{}` of enum `{name}` isn't a valid identifier",
+                                variant.name
+                            ));
+                        }
+                        if !unique.insert(&variant.name) {
+                            problems.push(format!(
+                                "variant `{}` of enum `{name}` is declared twice",
+                                variant.name
+                            ));
+                        }
+                        if let Some(fields) = &variant.fields {
+                            let owner = format!("{name}::{}", variant.name);
+                            validate_fields(&owner, fields, &mut problems);
+                        }
+                    }
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MacrosError::InvalidSchemaError(problems.join("; ")))
+        }
+    }
+}
+
+/// Checks names of types and variants, which can't be raw identifiers.
+pub(crate) fn is_type_name(name: &str) -> bool {
+    is_ident(name) && !KEYWORDS.contains(&name) && !RESERVED_IDENTS.contains(&name)
+}
+
+/// Checks names of fields, keywords are escaped with `r#`.
+pub(crate) fn is_field_name(name: &str) -> bool {
+    is_ident(name) && !RESERVED_IDENTS.contains(&name)
+}
+
+fn validate_fields(owner: &str, fields: &[ArgumentSchema], problems: &mut Vec<String>) {
+    let mut unique = HashSet::new();
+    for field in fields {
+        if !is_field_name(&field.name) {
+            problems.push(format!(
+                "field `{}` of type `{owner}` isn't a valid identifier",
+                field.name
+            ));
+        }
+        if !unique.insert(&field.name) {
+            problems.push(format!(
+                "field `{}` of type `{owner}` is declared twice",
+                field.name
+            ));
+        }
+    }
+}
+
+/// Name of the trait with helpers of the contract, e.g. `NftTokenHelpers` for `nft_token`.
+pub(crate) fn helper_trait_name(contract_name: &str) -> String {
+    format!("{}Helpers", to_pascal_case(contract_name))
+}
+
+/// Checks that contracts don't generate the same helper trait,
+/// e.g. `NftMarketHelpers` is generated for both `nft_market` and `nft__market`.
+/// `trait_names` keeps generated traits with their contract.
+pub(crate) fn check_trait_collisions(
+    trait_names: &mut HashMap<String, String>,
+    schema: &ContractSchema,
+) -> Result<(), MacrosError> {
+    let trait_name = helper_trait_name(&schema.name);
+    match trait_names.get(&trait_name) {
+        Some(existing) if existing == &schema.name => Err(MacrosError::InvalidSchemaError(
+            format!("contract `{existing}` is added twice"),
+        )),
+        Some(existing) => Err(MacrosError::InvalidSchemaError(format!(
+            "trait `{trait_name}` is generated for both `{existing}` and `{}`",
+            schema.name
+        ))),
+        None => {
+            trait_names.insert(trait_name, schema.name.clone());
+            Ok(())
+        }
+    }
+}
+
+/// Checks that types declared by several contracts have the same shape, because they are generated in the same scope,
+/// e.g. `Token` of both nft and market contracts. Returns types which haven't been declared by previous contracts.
+/// `type_names` keeps generated types with their contract.
+pub(crate) fn check_type_collisions<'a>(
+    type_names: &mut HashMap<String, (String, TypeSchema)>,
+    schema: &'a ContractSchema,
+) -> Result<Vec<&'a TypeSchema>, MacrosError> {
+    let mut problems = Vec::new();
+    let mut ret = Vec::new();
+    for ty in &schema.types {
+        match type_names.get(ty.name()) {
+            Some((_, existing)) if existing == ty => {}
+            Some((contract, _)) => problems.push(format!(
+                "type `{}` is declared differently by `{contract}` and `{}`",
+                ty.name(),
+                schema.name
+            )),
+            None => {
+                type_names.insert(ty.name().to_owned(), (schema.name.clone(), ty.clone()));
+                ret.push(ty);
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(ret)
+    } else {
+        Err(MacrosError::InvalidSchemaError(problems.join("; ")))
+    }
+}
+
+/// Checks that generated methods don't collide with methods of other contracts,
+/// e.g. `view_a_b_c` is generated for contract `a` with function `b_c`
+/// and contract `a_b` with function `c`.
+/// `method_names` keeps generated names with their contract and function.
+pub(crate) fn check_name_collisions(
+    method_names: &mut HashMap<String, String>,
+    blueprints: &[FunctionBlueprint],
+) -> Result<(), MacrosError> {
+    let mut problems = Vec::new();
+    for blueprint in blueprints {
+        let source = format!(
+            "{}.{}",
+            blueprint.contract_name, blueprint.contract_function_name
+        );
+        if let Some(existing) = method_names.get(&blueprint.trait_method_name) {
+            if existing != &source {
+                problems.push(format!(
+                    "method `{}` is generated for both `{existing}` and `{source}`",
+                    blueprint.trait_method_name
+                ));
+            }
+            continue;
+        }
+        method_names.insert(blueprint.trait_method_name.clone(), source);
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MacrosError::InvalidSchemaError(problems.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(json: &str) -> ContractSchema {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn pascal_case_works() {
+        assert_eq!(to_pascal_case("nft_token"), "NftToken");
+        assert_eq!(to_pascal_case("_market__v2"), "MarketV2");
+    }
+
+    #[test]
+    fn keywords_are_escaped() {
+        assert_eq!(escape_ident("type"), "r#type");
+        assert_eq!(escape_ident("token_id"), "token_id");
+    }
+
+    #[test]
+    fn invalid_schema_is_reported() {
+        let schema = schema(
+            r#"{
+                "name": "nft-contract",
+                "functions": [
+                    { "name": "mint", "kind": "call", "arguments": [{ "name": "self", "type": "u8" }] },
+                    { "name": "mint", "kind": "call", "arguments": [{ "name": "1st", "type": "u8" }] },
+                    { "name": "burn", "kind": "call", "arguments": [{ "name": "__lemotests_tx", "type": "u8" }] }
+                ]
+            }"#,
+        );
+
+        let error = schema.validate().unwrap_err().to_string();
+        assert!(error.contains("contract name `nft-contract`"));
+        assert!(error.contains("function `mint` is declared twice"));
+        assert!(error.contains("argument `self` of function `mint` is a reserved keyword"));
+        assert!(error.contains("argument `1st` of function `mint` isn't a valid identifier"));
+        assert!(error.contains("argument `__lemotests_tx` of function `burn` starts with"));
+    }
+
+    #[test]
+    fn keyword_arguments_are_valid() {
+        let schema = schema(
+            r#"{
+                "name": "nft",
+                "functions": [{ "name": "f", "kind": "view", "arguments": [{ "name": "type", "type": "u8" }] }]
+            }"#,
+        );
+
+        assert!(schema.validate().is_ok());
+        assert!(schema.blueprints(&[]).is_ok());
+    }
+
+    #[test]
+    fn name_collisions_are_reported() {
+        let mut method_names = HashMap::new();
+        let first = schema(
+            r#"{ "name": "a", "functions": [{ "name": "b_c", "kind": "view", "arguments": [] }] }"#,
+        );
+        let second = schema(
+            r#"{ "name": "a_b", "functions": [{ "name": "c", "kind": "view", "arguments": [] }] }"#,
+        );
+
+        check_name_collisions(&mut method_names, &first.blueprints(&[]).unwrap()).unwrap();
+        let error = check_name_collisions(&mut method_names, &second.blueprints(&[]).unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("method `view_a_b_c` is generated for both `a.b_c` and `a_b.c`"));
+    }
+
+    #[test]
+    fn invalid_types_are_reported() {
+        let schema = schema(
+            r#"{
+                "name": "market",
+                "functions": [],
+                "types": [
+                    { "kind": "struct", "name": "Sale", "fields": [{ "name": "self", "type": "u8" }] },
+                    { "kind": "enum", "name": "Sale", "variants": [{ "name": "Sold" }, { "name": "Sold" }] },
+                    { "kind": "enum", "name": "type", "variants": [{ "name": "A", "fields": [{ "name": "x", "type": "u8" }, { "name": "x", "type": "u8" }] }] }
+                ]
+            }"#,
+        );
+
+        let error = schema.validate().unwrap_err().to_string();
+        assert!(error.contains("type `Sale` is declared twice"));
+        assert!(error.contains("field `self` of type `Sale` isn't a valid identifier"));
+        assert!(error.contains("variant `Sold` of enum `Sale` is declared twice"));
+        assert!(error.contains("type name `type` isn't a valid identifier"));
+        assert!(error.contains("field `x` of type `type::A` is declared twice"));
+
+        let mut type_names = HashMap::new();
+        let other = r#"{ "name": "nft", "functions": [], "types": [{ "kind": "enum", "name": "Sale", "variants": [] }] }"#;
+        check_type_collisions(&mut type_names, &self::schema(other)).unwrap();
+        let error = check_type_collisions(&mut type_names, &schema)
+            .map(|_| ())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("type `Sale` is declared differently by `nft` and `market`"));
+    }
+
+    #[test]
+    fn identical_types_are_generated_once() {
+        let token = r#"{ "kind": "struct", "name": "Token", "fields": [{ "name": "token_id", "type": "String" }] }"#;
+        let nft = schema(&format!(
+            r#"{{ "name": "nft", "functions": [], "types": [{token}] }}"#
+        ));
+        let market = schema(&format!(
+            r#"{{ "name": "market", "functions": [], "types": [{token}, {{ "kind": "enum", "name": "Sale", "variants": [] }}] }}"#
+        ));
+
+        let mut type_names = HashMap::new();
+        let nft_types = check_type_collisions(&mut type_names, &nft).unwrap();
+        assert_eq!(
+            nft_types.iter().map(|ty| ty.name()).collect::<Vec<_>>(),
+            ["Token"]
+        );
+        let market_types = check_type_collisions(&mut type_names, &market).unwrap();
+        assert_eq!(
+            market_types.iter().map(|ty| ty.name()).collect::<Vec<_>>(),
+            ["Sale"]
+        );
+    }
+
+    #[test]
+    fn trait_collisions_are_reported() {
+        let mut trait_names = HashMap::new();
+        let first = schema(r#"{ "name": "nft_market", "functions": [] }"#);
+        let second = schema(r#"{ "name": "nft__market", "functions": [] }"#);
+
+        check_trait_collisions(&mut trait_names, &first).unwrap();
+        let error = check_trait_collisions(&mut trait_names, &second)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "trait `NftMarketHelpers` is generated for both `nft_market` and `nft__market`"
+        ));
+
+        let error = check_trait_collisions(&mut trait_names, &first)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("contract `nft_market` is added twice"));
+    }
+}
@@ -7,7 +7,7 @@ const NFT_PATH: &str =
 const MARKET_PATH: &str =
     "../../battlemon_contracts/target/wasm32-unknown-unknown/release/nft_market.wasm";
 
-add_helpers!("./lemotests/nft_schema.json");
+add_helpers!("../lemotests/nft_schema.json");
 
 #[tokio::test]
 async fn base_features_works() -> anyhow::Result<()> {
@@ -18,9 +18,10 @@ async fn base_features_works() -> anyhow::Result<()> {
         .with_bob(Near(10))?
         .build()
         .await?;
+    let bob_id = bchain.bob_id()?;
 
-    bchain
-        .alice_call_nft_token_init("alice")?
+    let (token, tx) = bchain
+        .call_nft_token_init("alice")?
         .with_gas(Tgas(10))
         .then()
         .alice_call_nft_token_nft_transfer("alice", "1", None, None)?
@@ -37,8 +38,12 @@ async fn base_features_works() -> anyhow::Result<()> {
         .then()
         .view_nft_token_nft_token("1")?
         .with_gas(Tgas(5))
-        .execute()
-        .await?;
+        .handle();
+    let result = tx.execute().await?;
+
+    let token = result.get(&token)?.expect("the token is minted");
+    assert_eq!(token["token_id"], "1");
+    assert_eq!(token["owner_id"], bob_id);
 
     Ok(())
 }
@@ -0,0 +1,339 @@
+//! Serde model of the ABI which near-sdk generates for contracts (`near-abi` format) and reading of the ABI
+//! embedded into wasm. JSON and borsh schemas of types are kept as `serde_json::Value`, unknown fields are ignored.
+use crate::HelperError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+
+/// Name of the wasm custom section with the ABI.
+pub const ABI_SECTION: &str = "near-abi";
+
+/// The magic number which starts zstd frames.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The limit of the decompressed ABI, so random bytes which look like a zstd frame can't exhaust memory.
+const MAX_ABI_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Reads the ABI embedded into the compiled contract, e.g. by `cargo near build --embed-abi`.
+pub fn from_wasm(path: impl AsRef<Path>) -> Result<AbiRoot, HelperError> {
+    let path = path.as_ref();
+    let wasm = std::fs::read(path)
+        .map_err(|e| HelperError::AbiError(format!("Failed to read `{}`: {e}", path.display())))?;
+
+    from_wasm_bytes(&wasm)
+}
+
+/// Reads the ABI from `near-abi` custom section of the wasm module.
+/// If there is no such section, the ABI is searched among zstd frames in the module,
+/// because near-sdk keeps the compressed ABI in data of the contract. The search stops at the first frame
+/// which is decompressed, so other bytes which look like frames aren't decompressed.
+pub fn from_wasm_bytes(wasm: &[u8]) -> Result<AbiRoot, HelperError> {
+    if !wasm.starts_with(b"\0asm") {
+        return Err(HelperError::AbiError(
+            "The file isn't a wasm module".to_owned(),
+        ));
+    }
+
+    if let Some(section) = custom_section(wasm, ABI_SECTION)? {
+        return parse_abi(section);
+    }
+
+    let json = (0..wasm.len())
+        .filter(|&start| wasm[start..].starts_with(&ZSTD_MAGIC))
+        .find_map(|start| decompress(&wasm[start..]).ok())
+        .ok_or_else(|| {
+            HelperError::AbiError(
+                "The contract doesn't contain the ABI, it should be built with the ABI embedded"
+                    .to_owned(),
+            )
+        })?;
+
+    deserialize_abi(&json)
+}
+
+/// Parses the ABI which is either zstd compressed or plain JSON.
+fn parse_abi(bytes: &[u8]) -> Result<AbiRoot, HelperError> {
+    if !bytes.starts_with(&ZSTD_MAGIC) {
+        return deserialize_abi(bytes);
+    }
+
+    deserialize_abi(&decompress(bytes)?)
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, HelperError> {
+    let decoder = ruzstd::StreamingDecoder::new(bytes)
+        .map_err(|e| HelperError::AbiError(format!("Failed to decompress: {e}")))?;
+    let mut ret = Vec::new();
+    decoder
+        .take(MAX_ABI_SIZE)
+        .read_to_end(&mut ret)
+        .map_err(|e| HelperError::AbiError(format!("Failed to decompress: {e}")))?;
+
+    Ok(ret)
+}
+
+fn deserialize_abi(json: &[u8]) -> Result<AbiRoot, HelperError> {
+    serde_json::from_slice(json)
+        .map_err(|e| HelperError::AbiError(format!("Failed to deserialize: {e}")))
+}
+
+/// Returns the content of the custom section, sections are `id: u8, size: leb128, content`.
+fn custom_section<'a>(wasm: &'a [u8], name: &str) -> Result<Option<&'a [u8]>, HelperError> {
+    let malformed = || HelperError::AbiError("The wasm module is malformed".to_owned());
+    let mut position = 8;
+    while position < wasm.len() {
+        let id = wasm[position];
+        position += 1;
+        let size = read_leb128(wasm, &mut position).ok_or_else(malformed)?;
+        let content = wasm.get(position..position + size).ok_or_else(malformed)?;
+        position += size;
+
+        if id != 0 {
+            continue;
+        }
+        let mut name_position = 0;
+        let name_len = read_leb128(content, &mut name_position).ok_or_else(malformed)?;
+        let section_name = content
+            .get(name_position..name_position + name_len)
+            .ok_or_else(malformed)?;
+        if section_name == name.as_bytes() {
+            return Ok(Some(&content[name_position + name_len..]));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reads unsigned LEB128 number which fits into `u32`.
+fn read_leb128(bytes: &[u8], position: &mut usize) -> Option<usize> {
+    let mut ret = 0usize;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*position)?;
+        *position += 1;
+        ret |= usize::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(ret);
+        }
+    }
+
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiRoot {
+    pub schema_version: String,
+    #[serde(default)]
+    pub metadata: AbiMetadata,
+    pub body: AbiBody,
+}
+
+impl AbiRoot {
+    /// Returns `true` if the JSON looks like the ABI rather than the `lemotests` schema.
+    pub fn is_abi(value: &Value) -> bool {
+        value.get("schema_version").is_some() && value.get("body").is_some()
+    }
+
+    /// Returns the schema of the type defined in `root_schema`, e.g. for `{"$ref": "#/definitions/U128"}`.
+    pub fn definition(&self, reference: &str) -> Option<&Value> {
+        let name = reference.strip_prefix("#/definitions/")?;
+        self.body.root_schema.get("definitions")?.get(name)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiMetadata {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiBody {
+    pub functions: Vec<AbiFunction>,
+    #[serde(default)]
+    pub root_schema: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiFunction {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    pub kind: AbiFunctionKind,
+    #[serde(default)]
+    pub modifiers: Vec<AbiFunctionModifier>,
+    #[serde(default)]
+    pub params: AbiParameters,
+    #[serde(default)]
+    pub result: Option<AbiType>,
+}
+
+impl AbiFunction {
+    pub fn has_modifier(&self, modifier: AbiFunctionModifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiFunctionKind {
+    View,
+    Call,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiFunctionModifier {
+    Init,
+    Private,
+    Payable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "serialization_type", rename_all = "lowercase")]
+pub enum AbiParameters {
+    Json { args: Vec<AbiParameter> },
+    Borsh { args: Vec<AbiParameter> },
+}
+
+impl Default for AbiParameters {
+    fn default() -> Self {
+        AbiParameters::Json { args: Vec::new() }
+    }
+}
+
+impl AbiParameters {
+    pub fn args(&self) -> &[AbiParameter] {
+        match self {
+            AbiParameters::Json { args } | AbiParameters::Borsh { args } => args,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiParameter {
+    pub name: String,
+    pub type_schema: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "serialization_type", rename_all = "lowercase")]
+pub enum AbiType {
+    Json { type_schema: Value },
+    Borsh { type_schema: Value },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ABI: &str =
+        r#"{"schema_version":"0.3.0","body":{"functions":[{"name":"get","kind":"view"}]}}"#;
+
+    /// Wraps bytes into a zstd frame with a single raw block.
+    fn zstd_frame(content: &[u8]) -> Vec<u8> {
+        let mut ret = ZSTD_MAGIC.to_vec();
+        // single segment, 2 bytes of content size which are stored minus 256
+        ret.push(0b0110_0000);
+        ret.extend_from_slice(&(content.len() as u16 - 256).to_le_bytes());
+        // the last raw block
+        let block_header = (content.len() as u32) << 3 | 1;
+        ret.extend_from_slice(&block_header.to_le_bytes()[..3]);
+        ret.extend_from_slice(content);
+        ret
+    }
+
+    fn wasm(sections: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut ret = b"\0asm\x01\0\0\0".to_vec();
+        for (id, content) in sections {
+            ret.push(*id);
+            ret.push(content.len() as u8 | 0x80);
+            ret.push((content.len() >> 7) as u8);
+            ret.extend_from_slice(content);
+        }
+        ret
+    }
+
+    fn custom(name: &str, content: &[u8]) -> (u8, Vec<u8>) {
+        let mut ret = vec![name.len() as u8];
+        ret.extend_from_slice(name.as_bytes());
+        ret.extend_from_slice(content);
+        (0, ret)
+    }
+
+    #[test]
+    fn abi_is_read_from_custom_section() {
+        let padded = format!("{ABI:<300}");
+        let wasm = wasm(&[
+            custom("name", b"nft"),
+            custom(ABI_SECTION, &zstd_frame(padded.as_bytes())),
+        ]);
+
+        let abi = from_wasm_bytes(&wasm).unwrap();
+        assert_eq!(abi.body.functions[0].name, "get");
+    }
+
+    #[test]
+    fn abi_is_found_in_data() {
+        let padded = format!("{ABI:<300}");
+        let mut data = b"garbage".to_vec();
+        data.extend_from_slice(&ZSTD_MAGIC);
+        data.extend_from_slice(&zstd_frame(padded.as_bytes()));
+        let wasm = wasm(&[(11, data)]);
+
+        let abi = from_wasm_bytes(&wasm).unwrap();
+        assert_eq!(abi.body.functions[0].name, "get");
+
+        let error = from_wasm_bytes(&self::wasm(&[(11, b"data".to_vec())])).unwrap_err();
+        assert!(error.to_string().contains("doesn't contain the ABI"));
+    }
+
+    #[test]
+    fn search_stops_at_first_decompressed_frame() {
+        let not_abi = format!("{:<300}", r#"{"key":"value"}"#);
+        let abi = format!("{ABI:<300}");
+        let mut data = zstd_frame(not_abi.as_bytes());
+        data.extend_from_slice(&zstd_frame(abi.as_bytes()));
+        let wasm = wasm(&[(11, data)]);
+
+        let error = from_wasm_bytes(&wasm).unwrap_err();
+        assert!(
+            error.to_string().contains("Failed to deserialize"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn abi_is_deserialized() {
+        let abi = json!({
+            "schema_version": "0.3.0",
+            "metadata": { "name": "nft-token", "version": "0.1.0" },
+            "body": {
+                "functions": [{
+                    "name": "nft_mint",
+                    "kind": "call",
+                    "modifiers": ["payable"],
+                    "params": {
+                        "serialization_type": "json",
+                        "args": [{ "name": "receiver_id", "type_schema": { "$ref": "#/definitions/AccountId" } }]
+                    },
+                    "result": { "serialization_type": "json", "type_schema": { "type": "string" } }
+                }],
+                "root_schema": { "definitions": { "AccountId": { "type": "string" } } }
+            }
+        });
+
+        assert!(AbiRoot::is_abi(&abi));
+        let abi: AbiRoot = serde_json::from_value(abi).unwrap();
+        let function = &abi.body.functions[0];
+        assert!(function.has_modifier(AbiFunctionModifier::Payable));
+        assert_eq!(function.params.args()[0].name, "receiver_id");
+        assert_eq!(
+            abi.definition("#/definitions/AccountId"),
+            Some(&json!({ "type": "string" }))
+        );
+    }
+}
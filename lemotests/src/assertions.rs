@@ -0,0 +1,186 @@
+use crate::{format_near, format_near_signed, ChainResult, Nearable, SignedNearable};
+
+/// Asserts that two amounts of NEAR differ by no more than `tolerance`.
+/// The tolerance is `ALMOST_ZERO` (0.1 NEAR) if it's omitted.
+///
+/// ```no_run
+/// use lemotests::prelude::*;
+///
+/// assert_near_eq!(Near(5), Near(5) - 10);
+/// assert_near_eq!(Near(5), Near(5) - 10, tolerance = 100);
+/// ```
+#[macro_export]
+macro_rules! assert_near_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_near_eq(&$actual, &$expected, &$crate::ALMOST_ZERO)
+    };
+    ($actual:expr, $expected:expr, tolerance = $tolerance:expr $(,)?) => {
+        $crate::assert_near_eq(&$actual, &$expected, &$tolerance)
+    };
+    ($actual:expr, $expected:expr, $tolerance:expr $(,)?) => {
+        $crate::assert_near_eq(&$actual, &$expected, &$tolerance)
+    };
+}
+
+/// Asserts that the balance of the account or contract has been changed by `expected`
+/// during the chain executed with `execute_tracked`. The tolerance is `ALMOST_ZERO` (0.1 NEAR) if it's omitted.
+///
+/// ```ignore
+/// use lemotests::prelude::*;
+///
+/// assert_balance_change!(result, ALICE, -Near(5).parse_signed());
+/// assert_balance_change!(result, BOB, Near(5), tolerance = 100);
+/// ```
+#[macro_export]
+macro_rules! assert_balance_change {
+    ($result:expr, $id:expr, $expected:expr $(,)?) => {
+        $crate::assert_balance_change(&$result, $id, &$expected, &$crate::ALMOST_ZERO)
+    };
+    ($result:expr, $id:expr, $expected:expr, tolerance = $tolerance:expr $(,)?) => {
+        $crate::assert_balance_change(&$result, $id, &$expected, &$tolerance)
+    };
+    ($result:expr, $id:expr, $expected:expr, $tolerance:expr $(,)?) => {
+        $crate::assert_balance_change(&$result, $id, &$expected, &$tolerance)
+    };
+}
+
+#[track_caller]
+pub fn assert_near_eq(actual: &impl Nearable, expected: &impl Nearable, tolerance: &impl Nearable) {
+    let (actual, expected, tolerance) = (actual.parse(), expected.parse(), tolerance.parse());
+    if actual.abs_diff(expected) > tolerance {
+        panic!(
+            "assertion failed: `actual ≈ expected`\n   \
+                actual: {}\n \
+                expected: {}\n     \
+                diff: {}\n\
+                tolerance: {}",
+            format_near(actual),
+            format_near(expected),
+            format_near_signed(actual as i128 - expected as i128),
+            format_near(tolerance),
+        );
+    }
+}
+
+#[track_caller]
+pub fn assert_balance_change<T>(
+    result: &ChainResult<T>,
+    id: impl AsRef<str>,
+    expected: &impl SignedNearable,
+    tolerance: &impl Nearable,
+) {
+    let id = id.as_ref();
+    let actual = result
+        .balance_changes()
+        .and_then(|changes| changes.delta(id))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let (expected, tolerance) = (expected.parse_signed(), tolerance.parse());
+    if actual.abs_diff(expected) > tolerance {
+        let steps = result
+            .step_deltas(id)
+            .into_iter()
+            .map(|(label, delta)| format!("\n    {label}: {}", format_near_signed(delta)))
+            .collect::<String>();
+
+        panic!(
+            "assertion failed: balance change of `{id}` isn't equal to expected\n   \
+                actual: {}\n \
+                expected: {}\n     \
+                diff: {}\n\
+                tolerance: {}\n\
+                changes by steps:{steps}",
+            format_near_signed(actual),
+            format_near_signed(expected),
+            format_near_signed(actual - expected),
+            format_near(tolerance),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::balance::{Snapshot, StepBalances};
+    use crate::chain_result::Key;
+    use crate::{ChainResult, Near, SignedNearable, TxDetails, ALICE};
+    use workspaces::network::Sandbox;
+
+    /// The chain where Alice starts with 10 NEAR and has `balance` after the step with `label`.
+    fn result(steps: &[(&str, Near)]) -> ChainResult<Sandbox> {
+        let snapshot = |balance: Near| Snapshot {
+            balances: [(ALICE.to_owned(), balance.as_yocto())]
+                .into_iter()
+                .collect(),
+            ..Snapshot::default()
+        };
+
+        let mut ret = ChainResult::new(Some(snapshot(Near(10))));
+        for (label, balance) in steps {
+            ret.add_tx_details(Key::Label(label.to_string()), TxDetails::Patch);
+            ret.add_step_balances(StepBalances {
+                snapshot: snapshot(*balance),
+                signer: None,
+                gas_cost: 0,
+            });
+        }
+        ret
+    }
+
+    #[test]
+    fn near_eq_within_tolerance_works() {
+        assert_near_eq!(Near(5), Near(5) - 10);
+        assert_near_eq!(Near(5) - 10, Near(5), tolerance = 10);
+        assert_near_eq!(100u128, 105u128, 5u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "actual: 4.9 N")]
+    fn near_eq_out_of_tolerance_fails() {
+        assert_near_eq!(Near(5) - 10u128.pow(23), Near(5), tolerance = 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "diff: -0.1 N\ntolerance: 0.000000000000000000000001 N")]
+    fn near_eq_failure_shows_diff_and_tolerance() {
+        assert_near_eq!(Near(5) - 10u128.pow(23), Near(5), 1u128);
+    }
+
+    #[test]
+    fn balance_change_within_tolerance_works() {
+        let result = result(&[("buy", Near(7)), ("refund", Near(5))]);
+        assert_balance_change!(result, ALICE, -Near(5).parse_signed());
+        assert_balance_change!(
+            result,
+            ALICE,
+            -Near(5).parse_signed() + 10,
+            tolerance = 10u128
+        );
+        assert_balance_change!(result, ALICE, -Near(5).parse_signed() - 10, 10u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "actual: -5 N\n expected: -4 N\n     diff: -1 N")]
+    fn balance_change_out_of_tolerance_fails() {
+        let result = result(&[("buy", Near(7)), ("refund", Near(5))]);
+        assert_balance_change!(result, ALICE, -Near(4).parse_signed(), tolerance = 1u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "changes by steps:\n    buy: -3 N\n    refund: -2 N")]
+    fn balance_change_failure_shows_steps() {
+        let result = result(&[("buy", Near(7)), ("refund", Near(5))]);
+        assert_balance_change!(result, ALICE, Near(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "carol")]
+    fn balance_change_of_unknown_account_fails() {
+        assert_balance_change!(result(&[]), "carol", Near(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "balances aren't tracked")]
+    fn balance_change_of_untracked_chain_fails() {
+        let result = ChainResult::<Sandbox>::new(None);
+        assert_balance_change!(result, ALICE, Near(1));
+    }
+}
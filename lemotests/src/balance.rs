@@ -0,0 +1,138 @@
+use crate::HelperError;
+use indexmap::IndexMap;
+
+pub(crate) type Balances = IndexMap<String, u128>;
+pub(crate) type StorageUsages = IndexMap<String, u64>;
+
+/// Balances and storage usage of all accounts and contracts at some point of the chain.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Snapshot {
+    pub(crate) balances: Balances,
+    pub(crate) storage_usage: StorageUsages,
+}
+
+impl Snapshot {
+    pub(crate) fn storage_usage(&self, id: &str) -> Result<u64, HelperError> {
+        self.storage_usage
+            .get(id)
+            .copied()
+            .ok_or_else(|| HelperError::AccountAndContractNotFound(id.to_owned()))
+    }
+}
+
+/// Balance of the account or contract before and after the chain or the step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub before: u128,
+    pub after: u128,
+    /// Gas paid by the account as a signer of the transactions,
+    /// approximated with `MIN_GAS_PRICE`, see `ChainResult::balance_changes`.
+    pub gas_cost: u128,
+}
+
+impl BalanceChange {
+    pub fn delta(&self) -> i128 {
+        self.after as i128 - self.before as i128
+    }
+
+    /// Change of the balance without gas costs, i.e. received minus sent tokens.
+    pub fn transferred(&self) -> i128 {
+        self.delta() + self.gas_cost as i128
+    }
+}
+
+/// Balance changes for every account and contract of the `State`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceChanges(IndexMap<String, BalanceChange>);
+
+impl BalanceChanges {
+    pub(crate) fn new(before: &Balances, after: &Balances, gas_costs: &Balances) -> Self {
+        let changes = after
+            .iter()
+            .map(|(id, &after)| {
+                let change = BalanceChange {
+                    before: before.get(id).copied().unwrap_or(after),
+                    after,
+                    gas_cost: gas_costs.get(id).copied().unwrap_or_default(),
+                };
+                (id.clone(), change)
+            })
+            .collect();
+
+        Self(changes)
+    }
+
+    pub fn get(&self, id: impl AsRef<str>) -> Result<&BalanceChange, HelperError> {
+        self.0
+            .get(id.as_ref())
+            .ok_or_else(|| HelperError::AccountAndContractNotFound(id.as_ref().to_owned()))
+    }
+
+    pub fn delta(&self, id: impl AsRef<str>) -> Result<i128, HelperError> {
+        self.get(id).map(BalanceChange::delta)
+    }
+
+    pub fn transferred(&self, id: impl AsRef<str>) -> Result<i128, HelperError> {
+        self.get(id).map(BalanceChange::transferred)
+    }
+
+    pub fn gas_cost(&self, id: impl AsRef<str>) -> Result<u128, HelperError> {
+        self.get(id).map(|change| change.gas_cost)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BalanceChange)> {
+        self.0.iter().map(|(id, change)| (id.as_str(), change))
+    }
+}
+
+/// Snapshot of the state after the step and the gas paid by the step signer.
+#[derive(Debug, Clone)]
+pub(crate) struct StepBalances {
+    pub(crate) snapshot: Snapshot,
+    pub(crate) signer: Option<String>,
+    pub(crate) gas_cost: u128,
+}
+
+impl StepBalances {
+    pub(crate) fn gas_costs(&self) -> Balances {
+        self.signer
+            .iter()
+            .map(|signer| (signer.clone(), self.gas_cost))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balances(items: &[(&str, u128)]) -> Balances {
+        items.iter().map(|(id, b)| (id.to_string(), *b)).collect()
+    }
+
+    #[test]
+    fn balance_changes_works() {
+        let before = balances(&[("alice", 100), ("bob", 50), ("market", 10)]);
+        let after = balances(&[("alice", 40), ("bob", 100), ("market", 15)]);
+        let gas_costs = balances(&[("alice", 5)]);
+        let changes = BalanceChanges::new(&before, &after, &gas_costs);
+
+        assert_eq!(changes.delta("alice").unwrap(), -60);
+        assert_eq!(changes.transferred("alice").unwrap(), -55);
+        assert_eq!(changes.gas_cost("alice").unwrap(), 5);
+        assert_eq!(changes.delta("bob").unwrap(), 50);
+        assert_eq!(changes.transferred("market").unwrap(), 5);
+        assert!(changes.get("charlie").is_err());
+    }
+
+    #[test]
+    fn step_gas_costs_belong_to_signer() {
+        let step = StepBalances {
+            snapshot: Snapshot::default(),
+            signer: Some("alice".to_owned()),
+            gas_cost: 7,
+        };
+
+        assert_eq!(step.gas_costs(), balances(&[("alice", 7)]));
+    }
+}
@@ -1,7 +1,13 @@
+use crate::balance::{Balances, Snapshot, StepBalances};
+use crate::tx_wrapper::execute_steps;
+use crate::Gas;
 use crate::TxDetails;
-use crate::{HelperError, State};
+use crate::TxWrapper;
+use crate::{format_near, BalanceChanges, ErrorKind, HelperError, State, StorageDiff, TxContext};
 use indexmap::IndexMap;
+use std::fmt::{self, Debug};
 use std::ops::Index;
+use workspaces::DevNetwork;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum Key {
@@ -9,24 +15,273 @@ pub(crate) enum Key {
     Index(usize),
 }
 
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Label(label) => write!(f, "{label}"),
+            Key::Index(idx) => write!(f, "#{idx}"),
+        }
+    }
+}
+
+/// The signer, receiver, method and deposit of the executed step.
+#[derive(Debug, Clone)]
+pub(crate) struct StepInfo {
+    pub(crate) context: TxContext,
+    pub(crate) deposit: u128,
+}
+
+/// The executed step of the chain.
+#[derive(Debug, Clone, Copy)]
+pub struct Step<'a> {
+    /// The label of the step or `#<index>` for unlabeled steps.
+    pub key: &'a str,
+    pub signer: Option<&'a str>,
+    pub receiver: Option<&'a str>,
+    pub method: &'a str,
+    pub deposit: u128,
+    pub details: &'a TxDetails,
+}
+
+impl Step<'_> {
+    /// Returns `true` if the transaction of the step or one of its receipts has failed.
+    pub fn is_failed(&self) -> bool {
+        match self.details {
+            TxDetails::Call(details) => !details.is_success(),
+            _ => false,
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match self.details {
+            TxDetails::Call(_) if self.is_failed() => "failed",
+            TxDetails::View(_) | TxDetails::ViewAccount(_) | TxDetails::ViewState(_) => "view",
+            _ => "ok",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ChainResult<T> {
     tx_results: IndexMap<Key, TxDetails>,
+    /// Balances before the chain, `None` if the chain isn't tracked.
+    initial_snapshot: Option<Snapshot>,
+    steps_balances: Vec<StepBalances>,
+    steps_info: Vec<StepInfo>,
     state: Option<State<T>>,
 }
 
 impl<T> ChainResult<T> {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(initial_snapshot: Option<Snapshot>) -> Self {
         Self {
             tx_results: IndexMap::new(),
+            initial_snapshot,
+            steps_balances: Vec::new(),
+            steps_info: Vec::new(),
             state: None,
         }
     }
 
+    /// Fails if any of `keys` is repeated or already taken by executed steps,
+    /// so duplicates are found before the steps are executed.
+    pub(crate) fn check_new_keys(&self, keys: &[Key]) -> Result<(), HelperError> {
+        for (idx, key) in keys.iter().enumerate() {
+            if self.tx_results.contains_key(key) || keys[..idx].contains(key) {
+                return Err(HelperError::ChainResultError(format!(
+                    "Tx with {:?} already exists",
+                    key
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn add_tx_details(&mut self, key: Key, tx_details: TxDetails) {
         self.tx_results.insert(key, tx_details);
     }
 
+    /// Returns `true` if balances are fetched after every step, see `TxWrapper::execute_tracked`.
+    pub fn is_tracked(&self) -> bool {
+        self.initial_snapshot.is_some()
+    }
+
+    pub(crate) fn add_step_balances(&mut self, step_balances: StepBalances) {
+        self.steps_balances.push(step_balances);
+    }
+
+    pub(crate) fn add_step_info(&mut self, step_info: StepInfo) {
+        self.steps_info.push(step_info);
+    }
+
+    /// Iterates over keys and results of steps in the order of execution.
+    /// The key is the label of the step or `#<index>` for unlabeled steps.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TxDetails)> {
+        self.steps().map(|step| (step.key, step.details))
+    }
+
+    /// Iterates over steps in the order of execution.
+    pub fn steps(&self) -> impl Iterator<Item = Step<'_>> {
+        self.steps_info
+            .iter()
+            .zip(self.tx_results.values())
+            .map(|(info, details)| Step {
+                key: &info.context.label,
+                signer: info.context.signer.as_deref(),
+                receiver: info.context.receiver.as_deref(),
+                method: &info.context.method,
+                deposit: info.deposit,
+                details,
+            })
+    }
+
+    /// Returns steps which called or viewed the `method`.
+    pub fn by_method<'a>(&'a self, method: &'a str) -> impl Iterator<Item = Step<'a>> {
+        self.steps().filter(move |step| step.method == method)
+    }
+
+    /// Returns steps signed by the account or contract with `signer` id.
+    pub fn by_signer<'a>(&'a self, signer: &'a str) -> impl Iterator<Item = Step<'a>> {
+        self.steps().filter(move |step| step.signer == Some(signer))
+    }
+
+    /// Returns steps received by the contract with `receiver` id.
+    pub fn by_receiver<'a>(&'a self, receiver: &'a str) -> impl Iterator<Item = Step<'a>> {
+        self.steps()
+            .filter(move |step| step.receiver == Some(receiver))
+    }
+
+    /// Returns steps with failed receipts.
+    pub fn failed(&self) -> impl Iterator<Item = Step<'_>> {
+        self.steps().filter(Step::is_failed)
+    }
+
+    /// Returns the result of the last step of the chain.
+    pub fn last(&self) -> Option<&TxDetails> {
+        self.tx_results.last().map(|(_, details)| details)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tx_results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tx_results.is_empty()
+    }
+
+    /// Returns balance changes of every account and contract
+    /// between the start and the end of the chain, it fails if the chain isn't tracked.
+    ///
+    /// Gas costs of signers are approximations: they are computed from the burnt gas with `MIN_GAS_PRICE`,
+    /// while the network charges the current gas price, which is higher on testnet under congestion.
+    /// The part of the fee which is rewarded to the receiving contract isn't subtracted from its balance change,
+    /// so `transferred` of contracts includes those rewards.
+    pub fn balance_changes(&self) -> Result<BalanceChanges, HelperError> {
+        let initial_snapshot = self.initial_snapshot()?;
+        let mut gas_costs = Balances::new();
+        for step in &self.steps_balances {
+            for (signer, gas_cost) in step.gas_costs() {
+                *gas_costs.entry(signer).or_default() += gas_cost;
+            }
+        }
+
+        Ok(BalanceChanges::new(
+            &initial_snapshot.balances,
+            &self.last_snapshot()?.balances,
+            &gas_costs,
+        ))
+    }
+
+    /// Returns the balance delta of the account or contract for every step.
+    pub(crate) fn step_deltas(&self, id: &str) -> Vec<(String, i128)> {
+        let mut before = self
+            .initial_snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.balances.get(id))
+            .copied()
+            .unwrap_or_default();
+        self.tx_results
+            .keys()
+            .zip(&self.steps_balances)
+            .map(|(key, step)| {
+                let after = step.snapshot.balances.get(id).copied().unwrap_or(before);
+                let delta = after as i128 - before as i128;
+                before = after;
+                (key.to_string(), delta)
+            })
+            .collect()
+    }
+
+    /// Returns balance changes of every account and contract made by the step with `label`.
+    pub fn step_balance_changes(
+        &self,
+        label: impl AsRef<str>,
+    ) -> Result<BalanceChanges, HelperError> {
+        let idx = self.step_index(label)?;
+        let before = self.snapshot_before(idx)?;
+        let step = &self.steps_balances[idx];
+
+        Ok(BalanceChanges::new(
+            &before.balances,
+            &step.snapshot.balances,
+            &step.gas_costs(),
+        ))
+    }
+
+    /// Returns the change of the storage used by the account or contract
+    /// between the start and the end of the chain, it fails if the chain isn't tracked.
+    pub fn storage_delta(&self, id: impl AsRef<str>) -> Result<i64, HelperError> {
+        let before = self.initial_snapshot()?.storage_usage(id.as_ref())?;
+        let after = self.last_snapshot()?.storage_usage(id.as_ref())?;
+        Ok(after as i64 - before as i64)
+    }
+
+    /// Returns the change of the storage used by the account or contract
+    /// made by the step with `label`, it fails if the chain isn't tracked.
+    pub fn step_storage_delta(
+        &self,
+        label: impl AsRef<str>,
+        id: impl AsRef<str>,
+    ) -> Result<i64, HelperError> {
+        let idx = self.step_index(label)?;
+        let before = self.snapshot_before(idx)?.storage_usage(id.as_ref())?;
+        let after = self.steps_balances[idx]
+            .snapshot
+            .storage_usage(id.as_ref())?;
+        Ok(after as i64 - before as i64)
+    }
+
+    fn step_index(&self, label: impl AsRef<str>) -> Result<usize, HelperError> {
+        self.tx_results
+            .get_index_of(&Key::Label(label.as_ref().to_owned()))
+            .ok_or_else(|| {
+                HelperError::ChainResultError(format!("No tx with label {}", label.as_ref()))
+            })
+    }
+
+    fn initial_snapshot(&self) -> Result<&Snapshot, HelperError> {
+        self.initial_snapshot.as_ref().ok_or_else(|| {
+            HelperError::ChainResultError(
+                "balances aren't tracked, execute the chain with `execute_tracked`".to_owned(),
+            )
+        })
+    }
+
+    fn snapshot_before(&self, idx: usize) -> Result<&Snapshot, HelperError> {
+        let initial_snapshot = self.initial_snapshot()?;
+        match idx {
+            0 => Ok(initial_snapshot),
+            _ => Ok(&self.steps_balances[idx - 1].snapshot),
+        }
+    }
+
+    fn last_snapshot(&self) -> Result<&Snapshot, HelperError> {
+        match self.steps_balances.last() {
+            Some(step) => Ok(&step.snapshot),
+            None => self.initial_snapshot(),
+        }
+    }
+
     pub fn tx(&self, label: impl AsRef<str>) -> Result<&TxDetails, HelperError> {
         self.tx_results
             .get(&Key::Label(label.as_ref().to_owned()))
@@ -35,13 +290,128 @@ impl<T> ChainResult<T> {
             })
     }
 
+    /// Returns the difference between storages fetched by `view_state` steps
+    /// with labels `from` and `to`, it fails if any of the steps isn't `view_state`.
+    pub fn storage_diff(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> Result<StorageDiff, HelperError> {
+        let from = self.tx(from)?.storage()?;
+        let to = self.tx(to)?.storage()?;
+        Ok(from.diff(to))
+    }
+
     pub(crate) fn add_state(&mut self, state: State<T>) {
         self.state = Some(state);
     }
 
+    pub fn state(&self) -> Result<&State<T>, HelperError> {
+        self.state.as_ref().ok_or_else(state_missing)
+    }
+
+    pub fn state_mut(&mut self) -> Result<&mut State<T>, HelperError> {
+        self.state.as_mut().ok_or_else(state_missing)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the state hasn't been added to the result, see `try_into_state`.
     pub fn into_state(self) -> State<T> {
-        self.state.unwrap()
+        self.try_into_state().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_into_state(self) -> Result<State<T>, HelperError> {
+        self.state.ok_or_else(state_missing)
+    }
+}
+
+impl<T> ChainResult<T>
+where
+    T: DevNetwork + Debug,
+{
+    /// Executes steps added by `f` to the state of the result and appends their results,
+    /// so labels of both chains can be queried together.
+    /// `f` can return `TxWrapper` or `TypedTx` from helpers of functions with results.
+    ///
+    /// ```ignore
+    /// let result = result
+    ///     .continue_with(|state| state.bob_call_market_contract_add_bid("1", None))
+    ///     .await?;
+    /// ```
+    pub async fn continue_with<F, W>(mut self, f: F) -> Result<Self, HelperError>
+    where
+        F: FnOnce(State<T>) -> Result<W, HelperError>,
+        W: Into<TxWrapper<T>>,
+    {
+        let state = self.state.take().ok_or_else(state_missing)?;
+        let tx: TxWrapper<T> = f(state)?.into();
+        let state = tx.then();
+        execute_steps(state, self).await
+    }
+}
+
+fn state_missing() -> HelperError {
+    HelperError::ChainResultError("The state hasn't been added to the result".to_owned())
+}
+
+/// Prints the summary table of steps, e.g. `println!("{result}")` for debugging failing tests.
+impl<T> fmt::Display for ChainResult<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self
+            .steps()
+            .map(|step| {
+                let (gas, logs) = match step.details {
+                    TxDetails::Call(details) => (
+                        Gas::from(details.total_gas_burnt).to_string(),
+                        details.logs().join("; "),
+                    ),
+                    _ => ("-".to_owned(), String::new()),
+                };
+
+                [
+                    step.key.to_owned(),
+                    step.signer.unwrap_or("-").to_owned(),
+                    step.receiver.unwrap_or("-").to_owned(),
+                    step.method.to_owned(),
+                    step.status().to_owned(),
+                    gas,
+                    format_near(step.deposit),
+                    logs,
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", format_table(&rows))
+    }
+}
+
+const SUMMARY_HEADER: [&str; 8] = [
+    "label", "signer", "receiver", "method", "status", "gas", "deposit", "logs",
+];
+
+/// Aligns columns of the table, the last column isn't padded.
+fn format_table(rows: &[[String; 8]]) -> String {
+    let mut widths = SUMMARY_HEADER.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
     }
+
+    let header = SUMMARY_HEADER.map(str::to_owned);
+    std::iter::once(&header)
+        .chain(rows)
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
 }
 
 impl<T> Index<usize> for ChainResult<T> {
@@ -67,3 +437,102 @@ impl<T> Contains for Result<ChainResult<T>, HelperError> {
         }
     }
 }
+
+/// Matches the reason of the chain failure.
+pub trait ExpectErrorKind {
+    fn has_error_kind(&self, kind: ErrorKind) -> bool;
+    /// Panics if the chain has succeeded or failed for another reason, returns the error otherwise.
+    fn expect_err_kind(self, kind: ErrorKind) -> HelperError;
+}
+
+impl<T> ExpectErrorKind for Result<ChainResult<T>, HelperError> {
+    fn has_error_kind(&self, kind: ErrorKind) -> bool {
+        match self {
+            Ok(_) => false,
+            Err(e) => kind.matches(e),
+        }
+    }
+
+    #[track_caller]
+    fn expect_err_kind(self, kind: ErrorKind) -> HelperError {
+        match self {
+            Ok(_) => panic!("expected the chain to fail with {kind:?}, but it has succeeded"),
+            Err(e) if kind.matches(&e) => e,
+            Err(e) => {
+                panic!("expected the chain to fail with {kind:?}, but it has failed with {e}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The chain where `nft` uses `usages[0]` bytes before the chain and `usages[i]` after the step `step{i}`.
+    fn result(usages: &[u64]) -> ChainResult<()> {
+        let snapshot = |usage: u64| Snapshot {
+            storage_usage: [("nft".to_owned(), usage)].into_iter().collect(),
+            ..Snapshot::default()
+        };
+
+        let mut ret = ChainResult::new(Some(snapshot(usages[0])));
+        for (idx, usage) in usages.iter().enumerate().skip(1) {
+            ret.add_tx_details(Key::Label(format!("step{idx}")), TxDetails::Patch);
+            ret.add_step_balances(StepBalances {
+                snapshot: snapshot(*usage),
+                signer: None,
+                gas_cost: 0,
+            });
+        }
+        ret
+    }
+
+    #[test]
+    fn storage_deltas_are_tracked() {
+        let result = result(&[100, 350, 300]);
+        assert_eq!(result.storage_delta("nft").unwrap(), 200);
+        assert_eq!(result.step_storage_delta("step1", "nft").unwrap(), 250);
+        assert_eq!(result.step_storage_delta("step2", "nft").unwrap(), -50);
+        assert!(result.storage_delta("market").is_err());
+    }
+
+    #[test]
+    fn storage_of_untracked_chain_isnt_available() {
+        let mut result = ChainResult::<()>::new(None);
+        for label in ["mint", "burn"] {
+            result.add_tx_details(Key::Label(label.to_owned()), TxDetails::Patch);
+        }
+
+        for error in [
+            result.storage_delta("nft").unwrap_err(),
+            result.step_storage_delta("burn", "nft").unwrap_err(),
+            result.step_balance_changes("burn").unwrap_err(),
+        ] {
+            assert!(error.to_string().contains("execute_tracked"), "{error}");
+        }
+    }
+
+    #[test]
+    fn duplicate_keys_are_rejected() {
+        let result = result(&[100, 350]);
+        let key = |label: &str| Key::Label(label.to_owned());
+        assert!(result.check_new_keys(&[key("mint"), Key::Index(1)]).is_ok());
+        assert!(result.check_new_keys(&[key("step1")]).is_err());
+        assert!(result.check_new_keys(&[key("mint"), key("mint")]).is_err());
+    }
+
+    #[test]
+    fn summary_table_is_aligned() {
+        let row = [
+            "mint", "alice", "nft", "nft_mint", "ok", "2.5 Tgas", "1 N", "",
+        ]
+        .map(str::to_owned);
+
+        assert_eq!(
+            format_table(&[row]),
+            "label | signer | receiver | method   | status | gas      | deposit | logs\n\
+             mint  | alice  | nft      | nft_mint | ok     | 2.5 Tgas | 1 N     |\n"
+        );
+    }
+}
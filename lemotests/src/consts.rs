@@ -7,6 +7,8 @@ pub const ALMOST_ZERO: Balance = 10_u128.pow(23); // 0.1 near
 // pub const TEN_NEAR: Balance = parse_near!("10 N");
 // pub const FIFTEEN_NEAR: Balance = parse_near!("15 N");
 // pub const SIXTEEN_NEAR: Balance = parse_near!("16 N");
+pub const STORAGE_PRICE_PER_BYTE: Balance = 10_u128.pow(19); // 0.00001 near
+pub const MIN_GAS_PRICE: Balance = 100_000_000; // yocto near per gas unit
 pub const ALICE: &str = "alice";
 pub const BOB: &str = "bob";
 pub const CHARLIE: &str = "charlie";
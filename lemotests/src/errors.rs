@@ -1,3 +1,7 @@
+use near_primitives::errors::{
+    ActionError, ActionErrorKind, FunctionCallErrorSer, HostError, InvalidAccessKeyError,
+    InvalidTxError, TxExecutionError,
+};
 use std::error::Error;
 use std::fmt::{self, Debug, Formatter};
 
@@ -19,6 +23,258 @@ pub enum HelperError {
     DestructuringError(String),
     #[error("ChainResultError was occured: {0}")]
     ChainResultError(String),
+    #[error("Failed to parse: {0}")]
+    ParseError(String),
+    #[error("Snapshot assertion failed: {0}")]
+    SnapshotError(String),
+    #[error("Failed to get ABI: {0}")]
+    AbiError(String),
+    #[error("Smart contract panicked in {context}: {message}")]
+    ContractPanic {
+        message: String,
+        context: Box<TxContext>,
+    },
+    #[error("Exceeded the prepaid gas in {context}: {message}")]
+    OutOfGas {
+        message: String,
+        context: Box<TxContext>,
+    },
+    #[error("Not enough deposit or balance in {context}: {message}")]
+    InsufficientDeposit {
+        message: String,
+        context: Box<TxContext>,
+    },
+    #[error("Account doesn't exist in {context}: {message}")]
+    MissingAccount {
+        message: String,
+        context: Box<TxContext>,
+    },
+    #[error("Access key doesn't exist in {context}: {message}")]
+    MissingAccessKey {
+        message: String,
+        context: Box<TxContext>,
+    },
+    #[error("RPC request failed in {context}: {message}")]
+    RpcError {
+        message: String,
+        context: Box<TxContext>,
+    },
+}
+
+/// The step of the chain which has failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxContext {
+    pub label: String,
+    pub signer: Option<String>,
+    pub receiver: Option<String>,
+    pub method: String,
+}
+
+impl fmt::Display for TxContext {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "step `{}` (", self.label)?;
+        if let Some(signer) = &self.signer {
+            write!(f, "{signer} -> ")?;
+        }
+        if let Some(receiver) = &self.receiver {
+            write!(f, "{receiver}.")?;
+        }
+        write!(f, "{})", self.method)
+    }
+}
+
+/// The reason of the failure used to match errors of the chain.
+/// `ContractPanic` matches errors whose panic message contains the provided substring.
+///
+/// ```ignore
+/// result.expect_err_kind(ErrorKind::ContractPanic("Not enough deposit"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind<'a> {
+    ContractPanic(&'a str),
+    OutOfGas,
+    InsufficientDeposit,
+    MissingAccount,
+    MissingAccessKey,
+    RpcError,
+}
+
+impl ErrorKind<'_> {
+    pub fn matches(&self, error: &HelperError) -> bool {
+        match (self, error) {
+            (ErrorKind::ContractPanic(expected), HelperError::ContractPanic { message, .. }) => {
+                message.contains(expected)
+            }
+            (ErrorKind::OutOfGas, HelperError::OutOfGas { .. })
+            | (ErrorKind::InsufficientDeposit, HelperError::InsufficientDeposit { .. })
+            | (ErrorKind::MissingAccount, HelperError::MissingAccount { .. })
+            | (ErrorKind::MissingAccessKey, HelperError::MissingAccessKey { .. })
+            | (ErrorKind::RpcError, HelperError::RpcError { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+impl HelperError {
+    /// Classifies the error returned by `workspaces` for the step.
+    /// Failed transactions are matched by the execution error, other errors (e.g. RPC failures)
+    /// are matched by the message.
+    pub(crate) fn from_tx_error(error: anyhow::Error, context: TxContext) -> Self {
+        match error.downcast_ref::<TxExecutionError>() {
+            Some(tx_error) => HelperError::from_execution_error(tx_error, context),
+            None => HelperError::from_error_message(format!("{error:#}"), context),
+        }
+    }
+
+    fn from_execution_error(error: &TxExecutionError, context: TxContext) -> Self {
+        let message = error.to_string();
+        let context = Box::new(context);
+        match error {
+            TxExecutionError::ActionError(ActionError { kind, .. }) => match kind {
+                ActionErrorKind::FunctionCallError(FunctionCallErrorSer::HostError(
+                    HostError::GuestPanic { panic_msg },
+                )) => HelperError::ContractPanic {
+                    message: panic_msg.clone(),
+                    context,
+                },
+                ActionErrorKind::FunctionCallError(FunctionCallErrorSer::ExecutionError(msg)) => {
+                    if let Some(panic_msg) = msg.strip_prefix("Smart contract panicked: ") {
+                        HelperError::ContractPanic {
+                            message: panic_msg.to_owned(),
+                            context,
+                        }
+                    } else if msg.contains("Exceeded the prepaid gas") {
+                        HelperError::OutOfGas { message, context }
+                    } else {
+                        HelperError::TransactionError(format!("{message} in {context}"))
+                    }
+                }
+                ActionErrorKind::FunctionCallError(FunctionCallErrorSer::HostError(
+                    HostError::GasExceeded | HostError::GasLimitExceeded,
+                )) => HelperError::OutOfGas { message, context },
+                ActionErrorKind::LackBalanceForState { .. } => {
+                    HelperError::InsufficientDeposit { message, context }
+                }
+                ActionErrorKind::AccountDoesNotExist { .. } => {
+                    HelperError::MissingAccount { message, context }
+                }
+                _ => HelperError::TransactionError(format!("{message} in {context}")),
+            },
+            TxExecutionError::InvalidTxError(tx_error) => match tx_error {
+                InvalidTxError::InvalidAccessKeyError(
+                    InvalidAccessKeyError::AccessKeyNotFound { .. },
+                ) => HelperError::MissingAccessKey { message, context },
+                InvalidTxError::InvalidAccessKeyError(
+                    InvalidAccessKeyError::NotEnoughAllowance { .. },
+                )
+                | InvalidTxError::NotEnoughBalance { .. }
+                | InvalidTxError::LackBalanceForState { .. } => {
+                    HelperError::InsufficientDeposit { message, context }
+                }
+                InvalidTxError::SignerDoesNotExist { .. } => {
+                    HelperError::MissingAccount { message, context }
+                }
+                _ => HelperError::TransactionError(format!("{message} in {context}")),
+            },
+        }
+    }
+
+    /// Classifies errors which aren't failed transactions by the message, e.g. failed views.
+    fn from_error_message(error: String, context: TxContext) -> Self {
+        let context = Box::new(context);
+        let contains_any = |patterns: &[&str]| patterns.iter().any(|p| error.contains(p));
+
+        if let Some(message) = panic_message(&error) {
+            HelperError::ContractPanic { message, context }
+        } else if contains_any(&[
+            "Exceeded the prepaid gas",
+            "GasExceeded",
+            "GasLimitExceeded",
+        ]) {
+            HelperError::OutOfGas {
+                message: error,
+                context,
+            }
+        } else if contains_any(&[
+            "NotEnoughBalance",
+            "LackBalanceForState",
+            "NotEnoughAllowance",
+        ]) {
+            HelperError::InsufficientDeposit {
+                message: error,
+                context,
+            }
+        } else if contains_any(&["AccessKeyNotFound", "InvalidAccessKeyError", "access key"]) {
+            HelperError::MissingAccessKey {
+                message: error,
+                context,
+            }
+        } else if contains_any(&["AccountDoesNotExist", "UnknownAccount", "does not exist"]) {
+            HelperError::MissingAccount {
+                message: error,
+                context,
+            }
+        } else {
+            HelperError::RpcError {
+                message: error,
+                context,
+            }
+        }
+    }
+
+    /// Classifies errors of views and sandbox steps which haven't been attached to the step yet.
+    pub(crate) fn in_step(self, context: TxContext) -> Self {
+        match self {
+            HelperError::UnexpectedError(error) => HelperError::from_tx_error(error, context),
+            error => error,
+        }
+    }
+
+    /// Returns the step of the chain which has failed, if the error has been produced by a step.
+    pub fn context(&self) -> Option<&TxContext> {
+        match self {
+            HelperError::ContractPanic { context, .. }
+            | HelperError::OutOfGas { context, .. }
+            | HelperError::InsufficientDeposit { context, .. }
+            | HelperError::MissingAccount { context, .. }
+            | HelperError::MissingAccessKey { context, .. }
+            | HelperError::RpcError { context, .. } => Some(context.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts the message of `env::panic_str`, `require!` or a rust panic from the error.
+fn panic_message(error: &str) -> Option<String> {
+    ["Smart contract panicked: ", "panic_msg: \""]
+        .iter()
+        .find_map(|pattern| {
+            error
+                .find(pattern)
+                .map(|idx| unquote(&error[idx + pattern.len()..]))
+        })
+}
+
+/// Takes the string until the first unescaped quote, unescaping `\"` and `\\`.
+fn unquote(s: &str) -> String {
+    let mut ret = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next @ ('"' | '\\')) => ret.push(next),
+                Some(next) => {
+                    ret.push(c);
+                    ret.push(next);
+                }
+                None => ret.push(c),
+            },
+            '"' => break,
+            _ => ret.push(c),
+        }
+    }
+
+    ret.trim_end().to_owned()
 }
 
 impl Debug for HelperError {
@@ -37,3 +293,181 @@ fn error_chain_fmt(error: &impl Error, f: &mut Formatter<'_>) -> fmt::Result {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(error: &str) -> HelperError {
+        HelperError::from_tx_error(anyhow::anyhow!(error.to_owned()), TxContext::default())
+    }
+
+    #[test]
+    fn contract_panic_message_is_extracted() {
+        let error = classify(
+            r#"Action #0: ExecutionError("Smart contract panicked: Not enough \"deposit\"")"#,
+        );
+        assert!(
+            matches!(&error, HelperError::ContractPanic { message, .. } if message == "Not enough \"deposit\"")
+        );
+        assert!(ErrorKind::ContractPanic("Not enough").matches(&error));
+        assert!(!ErrorKind::ContractPanic("Unauthorized").matches(&error));
+
+        let error = classify(
+            r#"wasm execution failed with error: FunctionCallError(HostError(GuestPanic { panic_msg: "Token not found" }))"#,
+        );
+        assert!(ErrorKind::ContractPanic("Token not found").matches(&error));
+    }
+
+    #[test]
+    fn errors_are_classified() {
+        let cases = [
+            ("Exceeded the prepaid gas.", ErrorKind::OutOfGas),
+            (
+                "LackBalanceForState { amount: 10 }",
+                ErrorKind::InsufficientDeposit,
+            ),
+            (
+                "account alice.test.near does not exist while viewing",
+                ErrorKind::MissingAccount,
+            ),
+            (
+                "InvalidAccessKeyError(AccessKeyNotFound)",
+                ErrorKind::MissingAccessKey,
+            ),
+            ("error sending request for url", ErrorKind::RpcError),
+        ];
+
+        for (error, kind) in cases {
+            assert!(kind.matches(&classify(error)), "{error} isn't {kind:?}");
+        }
+    }
+
+    fn classify_tx(error: TxExecutionError) -> HelperError {
+        HelperError::from_tx_error(anyhow::Error::new(error), TxContext::default())
+    }
+
+    fn action_error(kind: ActionErrorKind) -> TxExecutionError {
+        TxExecutionError::ActionError(ActionError {
+            index: Some(0),
+            kind,
+        })
+    }
+
+    #[test]
+    fn execution_errors_are_classified() {
+        let error = classify_tx(action_error(ActionErrorKind::FunctionCallError(
+            FunctionCallErrorSer::ExecutionError(
+                "Smart contract panicked: Not enough \"deposit\"".to_owned(),
+            ),
+        )));
+        assert!(
+            matches!(&error, HelperError::ContractPanic { message, .. } if message == "Not enough \"deposit\"")
+        );
+
+        let cases = [
+            (
+                action_error(ActionErrorKind::FunctionCallError(
+                    FunctionCallErrorSer::HostError(HostError::GuestPanic {
+                        panic_msg: "Token not found".to_owned(),
+                    }),
+                )),
+                ErrorKind::ContractPanic("Token not found"),
+            ),
+            (
+                action_error(ActionErrorKind::FunctionCallError(
+                    FunctionCallErrorSer::ExecutionError("Exceeded the prepaid gas.".to_owned()),
+                )),
+                ErrorKind::OutOfGas,
+            ),
+            (
+                action_error(ActionErrorKind::FunctionCallError(
+                    FunctionCallErrorSer::HostError(HostError::GasLimitExceeded),
+                )),
+                ErrorKind::OutOfGas,
+            ),
+            (
+                action_error(ActionErrorKind::LackBalanceForState {
+                    account_id: "alice.test.near".parse().unwrap(),
+                    amount: 10,
+                }),
+                ErrorKind::InsufficientDeposit,
+            ),
+            (
+                action_error(ActionErrorKind::AccountDoesNotExist {
+                    account_id: "alice.test.near".parse().unwrap(),
+                }),
+                ErrorKind::MissingAccount,
+            ),
+            (
+                TxExecutionError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                    InvalidAccessKeyError::AccessKeyNotFound {
+                        account_id: "alice.test.near".parse().unwrap(),
+                        public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                            .parse()
+                            .unwrap(),
+                    },
+                )),
+                ErrorKind::MissingAccessKey,
+            ),
+            (
+                TxExecutionError::InvalidTxError(InvalidTxError::NotEnoughBalance {
+                    signer_id: "alice.test.near".parse().unwrap(),
+                    balance: 1,
+                    cost: 10,
+                }),
+                ErrorKind::InsufficientDeposit,
+            ),
+            (
+                TxExecutionError::InvalidTxError(InvalidTxError::SignerDoesNotExist {
+                    signer_id: "alice.test.near".parse().unwrap(),
+                }),
+                ErrorKind::MissingAccount,
+            ),
+        ];
+
+        for (error, kind) in cases {
+            let message = error.to_string();
+            assert!(
+                kind.matches(&classify_tx(error)),
+                "{message} isn't {kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_execution_errors_arent_rpc_errors() {
+        let error = classify_tx(action_error(ActionErrorKind::AccountAlreadyExists {
+            account_id: "alice.test.near".parse().unwrap(),
+        }));
+        assert!(matches!(error, HelperError::TransactionError(_)));
+    }
+
+    #[test]
+    fn errors_of_steps_get_context() {
+        let context = TxContext {
+            label: "view_state".to_owned(),
+            ..TxContext::default()
+        };
+
+        let error = HelperError::UnexpectedError(anyhow::anyhow!("error sending request for url"))
+            .in_step(context.clone());
+        assert!(ErrorKind::RpcError.matches(&error));
+        assert_eq!(error.context(), Some(&context));
+
+        let error = HelperError::ContractNotFound("nft".to_owned()).in_step(context);
+        assert!(matches!(error, HelperError::ContractNotFound(id) if id == "nft"));
+    }
+
+    #[test]
+    fn context_is_displayed() {
+        let context = TxContext {
+            label: "mint".to_owned(),
+            signer: Some("alice".to_owned()),
+            receiver: Some("nft".to_owned()),
+            method: "nft_mint".to_owned(),
+        };
+
+        assert_eq!(context.to_string(), "step `mint` (alice -> nft.nft_mint)");
+    }
+}
@@ -1,22 +1,36 @@
+pub mod abi;
+mod assertions;
+mod balance;
 mod chain_result;
 pub mod consts;
 mod errors;
 pub mod prelude;
+mod sandbox;
+mod snapshot;
 mod state;
 mod state_builder;
+mod storage;
 mod tx_details;
 mod tx_wrapper;
+mod typed_tx;
 mod units;
 
 pub use anyhow;
+pub use assertions::*;
+pub use balance::{BalanceChange, BalanceChanges};
+pub use borsh;
 pub use chain_result::*;
 pub use consts::*;
 pub use errors::*;
+pub use sandbox::{AccountPatch, BlockInfo};
+pub use serde;
 pub use serde_json;
 pub use state::*;
 pub use state_builder::*;
+pub use storage::*;
 pub use tokio;
 pub use tx_details::*;
 pub use tx_wrapper::*;
+pub use typed_tx::{Handle, TypedTx};
 pub use units::*;
 pub use workspaces;
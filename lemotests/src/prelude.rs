@@ -1,3 +1,7 @@
 pub use crate::consts::*;
+pub use crate::AccountDetailsExt;
 pub use crate::Contains;
-pub use crate::{Near, StateBuilder, Tgas, TxDetails};
+pub use crate::{assert_balance_change, assert_near_eq};
+pub use crate::{ErrorKind, ExpectErrorKind};
+pub use crate::{Gas, Ggas, Near, StateBuilder, StorageBytes, Tgas, TxDetails, YoctoNear};
+pub use crate::{Nearable, SignedNearable};
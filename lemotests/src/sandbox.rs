@@ -0,0 +1,220 @@
+use crate::{HelperError, Nearable, State, TxArguments, TxDetails, TxKind, TxWrapper};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use workspaces::network::Sandbox;
+use workspaces::Account;
+
+pub(crate) type StepFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<TxDetails, HelperError>> + Send + 'a>>;
+type StepFn<T> = dyn for<'a> Fn(&'a State<T>) -> StepFuture<'a> + Send + Sync;
+
+/// The step which can't be expressed with the regular transaction,
+/// e.g. operations available only for the sandbox network.
+pub(crate) struct SandboxStep<T>(Box<StepFn<T>>);
+
+impl<T> SandboxStep<T> {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: for<'a> Fn(&'a State<T>) -> StepFuture<'a> + Send + Sync + 'static,
+    {
+        Self(Box::new(f))
+    }
+
+    pub(crate) fn run<'a>(&self, state: &'a State<T>) -> StepFuture<'a> {
+        (self.0)(state)
+    }
+}
+
+impl<T> Debug for SandboxStep<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SandboxStep")
+    }
+}
+
+/// Changes which `State::patch_account` applies to the account.
+#[derive(Debug, Clone, Default)]
+pub struct AccountPatch {
+    balance: Option<u128>,
+    storage: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl AccountPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn balance(mut self, balance: impl Nearable) -> Self {
+        self.balance = Some(balance.parse());
+        self
+    }
+
+    /// Writes the `value` under the `key` in the storage of the contract.
+    pub fn storage(mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Self {
+        self.storage
+            .push((key.as_ref().to_vec(), value.as_ref().to_vec()));
+        self
+    }
+}
+
+/// Height and timestamp (in nanoseconds) of the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockInfo {
+    pub height: u64,
+    pub timestamp: u64,
+}
+
+impl State<Sandbox> {
+    /// Produces `blocks` blocks right away, without adding a step to the chain.
+    pub async fn fast_forward(&self, blocks: u64) -> Result<(), HelperError> {
+        self.worker().fast_forward(blocks).await?;
+        Ok(())
+    }
+
+    /// Returns height and timestamp of the latest block.
+    pub async fn current_block(&self) -> Result<BlockInfo, HelperError> {
+        let block = self.worker().view_latest_block().await?;
+        let ret = BlockInfo {
+            height: block.height(),
+            timestamp: block.timestamp(),
+        };
+
+        Ok(ret)
+    }
+
+    /// Adds a step which produces `blocks` blocks.
+    /// The result of the step is the latest block after waiting.
+    pub fn wait_blocks(self, blocks: u64) -> Result<TxWrapper<Sandbox>, HelperError> {
+        let step = SandboxStep::new(move |state: &State<Sandbox>| {
+            Box::pin(async move {
+                state.fast_forward(blocks).await?;
+                let block = state.current_block().await?;
+                Ok(TxDetails::Block(block))
+            })
+        });
+
+        let ret = TxWrapper::new(
+            None,
+            None,
+            "wait_blocks".to_owned(),
+            TxArguments::Bytes(Vec::new()),
+            TxKind::WaitBlocks,
+            self,
+        )
+        .with_sandbox_step(step);
+
+        Ok(ret)
+    }
+
+    /// Adds a step which writes the `value` under the `key` in the storage of the contract.
+    pub fn patch_storage(
+        self,
+        contract: impl AsRef<str>,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<TxWrapper<Sandbox>, HelperError> {
+        let patch = AccountPatch::new().storage(key, value);
+        self.patch_step(contract, patch, "patch_storage")
+    }
+
+    /// Adds a step which sets the balance of the account or contract.
+    ///
+    /// The excess is sent back to the sandbox root account, which tops up the balance afterwards,
+    /// so the balance is exact even though the account pays the fee for sending the excess.
+    pub fn set_balance(
+        self,
+        id: impl AsRef<str>,
+        balance: impl Nearable,
+    ) -> Result<TxWrapper<Sandbox>, HelperError> {
+        let patch = AccountPatch::new().balance(balance);
+        self.patch_step(id, patch, "set_balance")
+    }
+
+    /// Adds a step which applies the `patch` to the account or contract.
+    pub fn patch_account(
+        self,
+        id: impl AsRef<str>,
+        patch: AccountPatch,
+    ) -> Result<TxWrapper<Sandbox>, HelperError> {
+        self.patch_step(id, patch, "patch_account")
+    }
+
+    fn patch_step(
+        self,
+        id: impl AsRef<str>,
+        patch: AccountPatch,
+        function: &str,
+    ) -> Result<TxWrapper<Sandbox>, HelperError> {
+        let id = id.as_ref().to_owned();
+        if self.signer(&id).is_none() {
+            return Err(HelperError::AccountAndContractNotFound(id));
+        }
+
+        if !patch.storage.is_empty() && self.contract_key(&id).is_none() {
+            return Err(HelperError::ContractNotFound(id));
+        }
+
+        let step_id = id.clone();
+        let step = SandboxStep::new(move |state: &State<Sandbox>| {
+            let id = step_id.clone();
+            let patch = patch.clone();
+            Box::pin(async move {
+                if let Some(balance) = patch.balance {
+                    state.apply_balance(&id, balance).await?;
+                }
+
+                for (key, value) in &patch.storage {
+                    let contract = state.contract(&id)?;
+                    state
+                        .worker()
+                        .patch_state(contract.id(), key, value)
+                        .await?;
+                }
+
+                Ok(TxDetails::Patch)
+            })
+        });
+
+        let ret = TxWrapper::new(
+            Some(id),
+            None,
+            function.to_owned(),
+            TxArguments::Bytes(Vec::new()),
+            TxKind::Patch,
+            self,
+        )
+        .with_sandbox_step(step);
+
+        Ok(ret)
+    }
+
+    async fn apply_balance(&self, id: &str, balance: u128) -> Result<(), HelperError> {
+        let current = self.view_account_details(id).await?.balance;
+        let signer = self
+            .signer(id)
+            .ok_or_else(|| HelperError::AccountAndContractNotFound(id.to_owned()))?;
+        let sandbox_root = self.worker().root_account();
+
+        if balance < current {
+            signer
+                .transfer_near(self.worker(), sandbox_root.id(), current - balance)
+                .await?;
+        }
+
+        // the account pays the fee for sending the excess back, the root covers the shortfall
+        let current = self.view_account_details(id).await?.balance;
+        if balance > current {
+            sandbox_root
+                .transfer_near(self.worker(), signer.id(), balance - current)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn signer(&self, id: &str) -> Option<&Account> {
+        self.account(id)
+            .ok()
+            .or_else(|| self.contract(id).ok().map(|contract| contract.as_account()))
+    }
+}
@@ -0,0 +1,232 @@
+use crate::units::GAS_PER_TGAS;
+use crate::{ChainResult, HelperError, Step, TxDetails};
+use serde_json::{json, Value};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use workspaces::DevNetwork;
+
+/// Set the env variable to `1` to overwrite snapshots instead of comparing them.
+const UPDATE_SNAPSHOTS_ENV: &str = "LEMOTESTS_UPDATE_SNAPSHOTS";
+const EVENT_PREFIX: &str = "EVENT_JSON:";
+const ROOT_PLACEHOLDER: &str = "<root>";
+
+impl<T: DevNetwork + Debug> ChainResult<T> {
+    /// Compares the normalized view of the result with `tests/snapshots/<name>.snap`.
+    /// The snapshot is created if it doesn't exist
+    /// and is overwritten if `LEMOTESTS_UPDATE_SNAPSHOTS=1`.
+    ///
+    /// Steps are stored with their labels, statuses, JSON results, logs and events.
+    /// The id of the root account is replaced with `<root>`, gas is rounded up to whole Tgas.
+    #[track_caller]
+    pub fn assert_snapshot(&self, name: impl AsRef<str>) {
+        let content = self.snapshot_content();
+        let update = matches!(env::var(UPDATE_SNAPSHOTS_ENV).as_deref(), Ok("1"));
+        if let Err(e) = assert_snapshot_content(&snapshot_path(name.as_ref()), &content, update) {
+            panic!("{e}");
+        }
+    }
+
+    /// Returns the normalized view of the result which is stored by `assert_snapshot`.
+    pub fn snapshot_content(&self) -> String {
+        let steps = self.steps().map(step_snapshot).collect::<Vec<_>>();
+        let content = serde_json::to_string_pretty(&steps).expect("Failed to serialize snapshot");
+        match self.state() {
+            Ok(state) => redact_root(&content, state.root().id().as_str()),
+            Err(_) => content,
+        }
+    }
+}
+
+fn step_snapshot(step: Step) -> Value {
+    let mut ret = json!({
+        "label": step.key,
+        "signer": step.signer,
+        "receiver": step.receiver,
+        "method": step.method,
+        "status": if step.is_failed() { "failed" } else { "ok" },
+    });
+
+    let extra = match step.details {
+        TxDetails::Call(details) => {
+            let (events, logs) = split_events(details.logs());
+            json!({
+                "result": json_result(step.details),
+                "gas": bucket_gas(details.total_gas_burnt),
+                "logs": logs,
+                "events": events,
+            })
+        }
+        TxDetails::View(details) => json!({
+            "result": json_result(step.details),
+            "logs": details.logs,
+        }),
+        TxDetails::ViewAccount(details) => json!({ "storage_usage": details.storage_usage }),
+        TxDetails::ViewState(storage) => json!({ "keys": storage.keys_utf8() }),
+        TxDetails::Patch | TxDetails::Block(_) => json!({}),
+    };
+
+    if let (Value::Object(ret), Value::Object(extra)) = (&mut ret, extra) {
+        ret.extend(extra);
+    }
+
+    ret
+}
+
+fn json_result(details: &TxDetails) -> Value {
+    match details.raw_bytes() {
+        Ok(bytes) if bytes.is_empty() => Value::Null,
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(format!("<{} bytes>", bytes.len()))),
+        Err(e) => Value::String(format!("<{e}>")),
+    }
+}
+
+/// Splits NEP-297 events from plain logs.
+fn split_events<'a>(logs: impl IntoIterator<Item = &'a str>) -> (Vec<Value>, Vec<String>) {
+    let mut events = Vec::new();
+    let mut plain = Vec::new();
+    for log in logs {
+        match log
+            .strip_prefix(EVENT_PREFIX)
+            .and_then(|event| serde_json::from_str(event.trim()).ok())
+        {
+            Some(event) => events.push(event),
+            None => plain.push(log.to_owned()),
+        }
+    }
+
+    (events, plain)
+}
+
+fn bucket_gas(gas: u64) -> String {
+    let tgas = match gas % GAS_PER_TGAS {
+        0 => gas / GAS_PER_TGAS,
+        _ => gas / GAS_PER_TGAS + 1,
+    };
+    format!("<= {tgas} Tgas")
+}
+
+/// Replaces the root account id, so ids like `alice.dev-20220101-1234.test.near` are stable.
+fn redact_root(content: &str, root: &str) -> String {
+    if root.is_empty() {
+        return content.to_owned();
+    }
+
+    content.replace(root, ROOT_PLACEHOLDER)
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    let dir = env::var("CARGO_MANIFEST_DIR").map_or_else(|_| PathBuf::from("."), PathBuf::from);
+    dir.join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"))
+}
+
+fn assert_snapshot_content(path: &Path, content: &str, update: bool) -> Result<(), HelperError> {
+    let snapshot_error = |e: std::io::Error| {
+        HelperError::SnapshotError(format!("Failed to access {}: {e}", path.display()))
+    };
+
+    if !update {
+        if let Ok(expected) = fs::read_to_string(path) {
+            let expected = expected.trim_end();
+            if expected == content.trim_end() {
+                return Ok(());
+            }
+
+            return Err(HelperError::SnapshotError(format!(
+                "snapshot {} doesn't match, set {UPDATE_SNAPSHOTS_ENV}=1 to update it\n{}",
+                path.display(),
+                line_diff(expected, content.trim_end())
+            )));
+        }
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(snapshot_error)?;
+    }
+    fs::write(path, format!("{}\n", content.trim_end())).map_err(snapshot_error)
+}
+
+/// Returns lines of both texts prefixed with `-` for removed and `+` for added lines.
+fn line_diff(old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lengths of the longest common subsequences of suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ret = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ret.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ret.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            ret.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_diff_works() {
+        let diff = line_diff("a\nb\nc", "a\nc\nd");
+        assert_eq!(diff, "  a\n- b\n  c\n+ d\n");
+    }
+
+    #[test]
+    fn normalization_works() {
+        assert_eq!(
+            redact_root(r#""alice.dev-1234.test.near""#, "dev-1234.test.near"),
+            r#""alice.<root>""#
+        );
+        assert_eq!(bucket_gas(2_500_000_000_000), "<= 3 Tgas");
+
+        let (events, logs) = split_events([
+            r#"EVENT_JSON:{"standard":"nep171","event":"nft_mint"}"#,
+            "Transfer 1 token",
+        ]);
+        assert_eq!(
+            events,
+            vec![json!({"standard": "nep171", "event": "nft_mint"})]
+        );
+        assert_eq!(logs, vec!["Transfer 1 token"]);
+    }
+
+    #[test]
+    fn snapshot_is_created_and_compared() {
+        let path = env::temp_dir()
+            .join(format!("lemotests-{}", std::process::id()))
+            .join("result.snap");
+
+        assert_snapshot_content(&path, "a\nb", false).unwrap();
+        assert_snapshot_content(&path, "a\nb", false).unwrap();
+        let error = assert_snapshot_content(&path, "a\nc", false).unwrap_err();
+        assert!(error.to_string().contains("- b\n+ c"));
+        assert_snapshot_content(&path, "a\nc", true).unwrap();
+        assert_snapshot_content(&path, "a\nc", false).unwrap();
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
+use crate::balance::Snapshot;
 use crate::{HelperError, TxKind, TxWrapper};
 use std::fmt::Debug;
-use workspaces::{Account, Contract, DevNetwork, Worker};
+use workspaces::{Account, AccountDetails, Contract, DevNetwork, Worker};
 pub type Accounts = indexmap::IndexMap<String, Account>;
 pub type Contracts = indexmap::IndexMap<String, Contract>;
 
@@ -11,6 +12,8 @@ pub struct State<T> {
     accounts: Accounts,
     contracts: Contracts,
     tx_scenarios: Option<Vec<TxWrapper<T>>>,
+    /// The number of labels generated for handles, so they are unique for the whole chain.
+    generated_labels: usize,
 }
 
 impl<T> State<T>
@@ -30,9 +33,16 @@ where
             accounts,
             contracts,
             tx_scenarios: Some(tx_scenarios),
+            generated_labels: 0,
         }
     }
 
+    /// Generates the label like `nft_token@1`, counters aren't reset by `continue_with`.
+    pub(crate) fn generate_label(&mut self, function: &str) -> String {
+        self.generated_labels += 1;
+        format!("{function}@{}", self.generated_labels)
+    }
+
     pub fn add_tx_scenario(&mut self, tx: TxWrapper<T>) {
         self.tx_scenarios.get_or_insert(Vec::new()).push(tx);
     }
@@ -87,6 +97,61 @@ where
         Ok(ret)
     }
 
+    /// Adds a step which fetches key-value storage of the contract.
+    /// Only keys starting with `prefix` are returned, an empty prefix returns the whole storage.
+    pub fn view_state(
+        self,
+        contract: impl AsRef<str>,
+        prefix: impl AsRef<[u8]>,
+    ) -> Result<TxWrapper<T>, HelperError> {
+        let contract = self
+            .contract_key(contract.as_ref())
+            .cloned()
+            .ok_or_else(|| HelperError::ContractNotFound(contract.as_ref().to_owned()))?;
+
+        let ret = TxWrapper::new(
+            None,
+            Some(contract),
+            "view_state".to_owned(),
+            prefix.as_ref().to_vec(),
+            TxKind::ViewState,
+            self,
+        );
+
+        Ok(ret)
+    }
+
+    /// Fetches details of the account or contract with `id` right away,
+    /// without adding a step to the chain.
+    pub async fn view_account_details(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<AccountDetails, HelperError> {
+        let id = id.as_ref();
+        let ret = match (self.accounts.get(id), self.contracts.get(id)) {
+            (Some(account), _) => account.view_account(&self.worker).await?,
+            (None, Some(contract)) => contract.view_account(&self.worker).await?,
+            (None, None) => return Err(HelperError::AccountAndContractNotFound(id.to_owned())),
+        };
+
+        Ok(ret)
+    }
+
+    /// Fetches balances and storage usage of all accounts and contracts of the state.
+    /// Accounts which can't be viewed, e.g. deleted by the step, are left out of the snapshot,
+    /// so the chain isn't aborted after its transactions have succeeded.
+    pub(crate) async fn snapshot(&self) -> Snapshot {
+        let mut ret = Snapshot::default();
+        for id in self.accounts.keys().chain(self.contracts.keys()) {
+            if let Ok(details) = self.view_account_details(id).await {
+                ret.balances.insert(id.clone(), details.balance);
+                ret.storage_usage.insert(id.clone(), details.storage_usage);
+            }
+        }
+
+        ret
+    }
+
     /// Returns ids for contracts and accounts.
     /// The order starts from contract ids and then accounts ids.
     /// The order of ids inside the group is the order of ids in your code.
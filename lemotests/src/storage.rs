@@ -0,0 +1,160 @@
+use crate::HelperError;
+use anyhow::Context;
+use borsh::BorshDeserialize;
+use std::collections::BTreeMap;
+
+pub type StorageEntries = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Key-value storage of the contract, the result of `State::view_state` step.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageView {
+    entries: StorageEntries,
+}
+
+impl StorageView {
+    pub fn new(entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> Self {
+        Self {
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    pub fn entries(&self) -> &StorageEntries {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries
+            .iter()
+            .map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<&[u8]> {
+        self.entries.get(key.as_ref()).map(Vec::as_slice)
+    }
+
+    pub fn get_utf8(&self, key: impl AsRef<[u8]>) -> Result<Option<String>, HelperError> {
+        self.get(key).map(decode_utf8).transpose()
+    }
+
+    pub fn get_borsh<T: BorshDeserialize>(
+        &self,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<T>, HelperError> {
+        self.get(key).map(decode_borsh).transpose()
+    }
+
+    /// Keys of the storage as strings, invalid UTF-8 sequences are replaced.
+    pub fn keys_utf8(&self) -> Vec<String> {
+        self.entries
+            .keys()
+            .map(|key| String::from_utf8_lossy(key).into_owned())
+            .collect()
+    }
+
+    /// Returns changes which turn `self` into `newer`.
+    pub fn diff(&self, newer: &StorageView) -> StorageDiff {
+        let mut ret = StorageDiff::default();
+        for (key, value) in &self.entries {
+            match newer.entries.get(key) {
+                None => {
+                    ret.removed.insert(key.clone(), value.clone());
+                }
+                Some(new_value) if new_value != value => {
+                    ret.changed
+                        .insert(key.clone(), (value.clone(), new_value.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (key, value) in &newer.entries {
+            if !self.entries.contains_key(key) {
+                ret.added.insert(key.clone(), value.clone());
+            }
+        }
+
+        ret
+    }
+}
+
+/// Difference between two `StorageView`s.
+/// `changed` holds the old and the new value of the key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageDiff {
+    pub added: StorageEntries,
+    pub removed: StorageEntries,
+    pub changed: BTreeMap<Vec<u8>, (Vec<u8>, Vec<u8>)>,
+}
+
+impl StorageDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub fn decode_utf8(bytes: &[u8]) -> Result<String, HelperError> {
+    let ret =
+        String::from_utf8(bytes.to_vec()).context("Failed to decode storage bytes as UTF-8")?;
+    Ok(ret)
+}
+
+pub fn decode_borsh<T: BorshDeserialize>(bytes: &[u8]) -> Result<T, HelperError> {
+    let ret = T::try_from_slice(bytes).context("Failed to decode storage bytes with borsh")?;
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    fn view(entries: &[(&str, &str)]) -> StorageView {
+        StorageView::new(
+            entries
+                .iter()
+                .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec())),
+        )
+    }
+
+    #[test]
+    fn diff_works() {
+        let before = view(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let after = view(&[("a", "1"), ("b", "20"), ("d", "4")]);
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.added.get(b"d".as_slice()), Some(&b"4".to_vec()));
+        assert_eq!(diff.removed.get(b"c".as_slice()), Some(&b"3".to_vec()));
+        assert_eq!(
+            diff.changed.get(b"b".as_slice()),
+            Some(&(b"2".to_vec(), b"20".to_vec()))
+        );
+        assert!(!diff.changed.contains_key(b"a".as_slice()));
+    }
+
+    #[test]
+    fn diff_of_equal_views_is_empty() {
+        let storage = view(&[("a", "1")]);
+        assert!(storage.diff(&storage.clone()).is_empty());
+    }
+
+    #[test]
+    fn decoding_works() {
+        let mut storage = view(&[("STATE", "owner")]);
+        storage
+            .entries
+            .insert(b"n".to_vec(), 42u64.try_to_vec().unwrap());
+
+        assert_eq!(storage.get_utf8("STATE").unwrap().unwrap(), "owner");
+        assert_eq!(storage.get_borsh::<u64>("n").unwrap().unwrap(), 42);
+        assert!(storage.get_borsh::<u64>("missing").unwrap().is_none());
+        assert!(storage.get_borsh::<u64>("STATE").is_err());
+    }
+}
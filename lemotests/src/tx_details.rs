@@ -1,5 +1,8 @@
+use crate::{BlockInfo, Gas, HelperError, StorageView, MIN_GAS_PRICE, STORAGE_PRICE_PER_BYTE};
+use borsh::BorshDeserialize;
 use std::fmt::Debug;
 use workspaces::result::{CallExecutionDetails, ExecutionOutcome, ViewResultDetails};
+use workspaces::types::CryptoHash;
 use workspaces::AccountDetails;
 
 #[derive(Debug)]
@@ -7,22 +10,105 @@ pub enum TxDetails {
     Call(Box<CallExecutionDetails>),
     View(ViewResultDetails),
     ViewAccount(AccountDetails),
+    ViewState(StorageView),
+    Patch,
+    Block(BlockInfo),
 }
 
 impl TxDetails {
+    /// # Panics
+    ///
+    /// Panics if the step isn't `view_account`, see `account_details`.
     pub fn balance(&self) -> u128 {
+        self.expect_account_details().balance
+    }
+
+    /// Details of the account for `view_account` steps.
+    pub fn account_details(&self) -> Result<&AccountDetails, HelperError> {
         match self {
-            TxDetails::Call(_) => unimplemented!("balance method not available for `Call`"),
-            TxDetails::View(_) => unimplemented!("balance method not available for `View`"),
-            TxDetails::ViewAccount(details) => details.balance,
+            TxDetails::ViewAccount(details) => Ok(details),
+            _ => Err(HelperError::ChainResultError(format!(
+                "account details are available only for `view_account` steps, got {}",
+                self.kind()
+            ))),
         }
     }
 
+    /// # Panics
+    ///
+    /// Panics if the step isn't `view_account`, see `account_details`.
+    pub fn storage_usage(&self) -> u64 {
+        self.expect_account_details().storage_usage
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the step isn't `view_account`, see `account_details`.
+    pub fn locked(&self) -> u128 {
+        self.expect_account_details().locked
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the step isn't `view_account`, see `account_details`.
+    pub fn code_hash(&self) -> &CryptoHash {
+        &self.expect_account_details().code_hash
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the step isn't `view_account`, see `account_details`.
+    pub fn available_balance(&self) -> u128 {
+        self.expect_account_details().available_balance()
+    }
+
     pub fn json<T: serde::de::DeserializeOwned>(&self) -> anyhow::Result<T> {
         match self {
             TxDetails::Call(details) => details.json(),
             TxDetails::View(details) => details.json(),
-            TxDetails::ViewAccount(_) => unimplemented!("json not available for ViewAccount"),
+            TxDetails::ViewAccount(_) => anyhow::bail!("json not available for ViewAccount"),
+            TxDetails::ViewState(_) => anyhow::bail!("json not available for ViewState"),
+            TxDetails::Patch => anyhow::bail!("json not available for Patch"),
+            TxDetails::Block(_) => anyhow::bail!("json not available for Block"),
+        }
+    }
+
+    pub fn borsh<T: BorshDeserialize>(&self) -> anyhow::Result<T> {
+        let bytes = self.raw_bytes()?;
+        let ret = T::try_from_slice(&bytes)?;
+        Ok(ret)
+    }
+
+    pub fn raw_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        match self {
+            TxDetails::Call(details) => details.raw_bytes(),
+            TxDetails::View(details) => Ok(details.result.clone()),
+            TxDetails::ViewAccount(_) => anyhow::bail!("raw_bytes not available for ViewAccount"),
+            TxDetails::ViewState(_) => anyhow::bail!("raw_bytes not available for ViewState"),
+            TxDetails::Patch => anyhow::bail!("raw_bytes not available for Patch"),
+            TxDetails::Block(_) => anyhow::bail!("raw_bytes not available for Block"),
+        }
+    }
+
+    /// Storage of the contract for `view_state` steps.
+    pub fn storage(&self) -> Result<&StorageView, HelperError> {
+        match self {
+            TxDetails::ViewState(storage) => Ok(storage),
+            _ => Err(HelperError::ChainResultError(format!(
+                "storage is available only for `view_state` steps, got {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    /// The block reached by `wait_blocks` steps.
+    pub fn block(&self) -> Result<&BlockInfo, HelperError> {
+        match self {
+            TxDetails::Block(block) => Ok(block),
+            _ => Err(HelperError::ChainResultError(format!(
+                "block is available only for `wait_blocks` steps, got {}",
+                self.kind()
+            ))),
         }
     }
 
@@ -54,12 +140,57 @@ impl TxDetails {
         }
     }
 
-    pub fn gas_used(&self) -> u64 {
-        // match self {
-        //     TxDetails::Call(details) => details.gas_used(),
-        //     _ => unimplemented!("View result has no gas used"),
-        // }
+    /// Gas burnt by the transaction of call steps.
+    pub fn gas_used(&self) -> Result<Gas, HelperError> {
+        match self {
+            TxDetails::Call(details) => Ok(Gas::from(details.total_gas_burnt)),
+            _ => Err(HelperError::ChainResultError(format!(
+                "gas used is available only for `Call` steps, got {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    fn expect_account_details(&self) -> &AccountDetails {
+        self.account_details().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            TxDetails::Call(_) => "`Call`",
+            TxDetails::View(_) => "`View`",
+            TxDetails::ViewAccount(_) => "`ViewAccount`",
+            TxDetails::ViewState(_) => "`ViewState`",
+            TxDetails::Patch => "`Patch`",
+            TxDetails::Block(_) => "`Block`",
+        }
+    }
+
+    /// The approximate amount of yocto NEAR paid for the burnt gas, computed with the minimal gas price
+    /// which sandbox uses. The actual price can be higher on testnet under congestion.
+    /// It's zero for steps without transactions.
+    pub fn gas_cost(&self) -> u128 {
+        match self {
+            TxDetails::Call(details) => u128::from(details.total_gas_burnt) * MIN_GAS_PRICE,
+            _ => 0,
+        }
+    }
+}
+
+pub trait AccountDetailsExt {
+    /// The amount of yocto NEAR required to cover the storage used by the account.
+    fn storage_cost(&self) -> u128;
+    /// The balance which can be spent, i.e. without the part reserved for the storage staking.
+    fn available_balance(&self) -> u128;
+}
+
+impl AccountDetailsExt for AccountDetails {
+    fn storage_cost(&self) -> u128 {
+        u128::from(self.storage_usage) * STORAGE_PRICE_PER_BYTE
+    }
 
-        todo!()
+    fn available_balance(&self) -> u128 {
+        let reserved = self.storage_cost().saturating_sub(self.locked);
+        self.balance.saturating_sub(reserved)
     }
 }
@@ -1,9 +1,12 @@
-use crate::chain_result::ChainResult;
+use crate::balance::StepBalances;
+use crate::chain_result::{ChainResult, StepInfo};
+use crate::sandbox::SandboxStep;
 use crate::tx_details::TxDetails;
 use crate::Key;
-use crate::{Gasable, HelperError, Nearable, State};
+use crate::StorageView;
+use crate::{format_near, Gas, Gasable, HelperError, Nearable, State, TxContext};
 use anyhow::Context;
-use serde::{Deserialize, Serialize};
+use borsh::BorshSerialize;
 use serde_json::Value;
 use std::fmt::Debug;
 use workspaces::DevNetwork;
@@ -14,6 +17,42 @@ pub enum TxKind {
     View,
     SelfContractCall,
     ViewAccount,
+    ViewState,
+    Patch,
+    WaitBlocks,
+}
+
+/// Arguments of the function call.
+/// JSON object is used by default, `Bytes` keeps already serialized arguments (e.g. borsh).
+#[derive(Debug, Clone)]
+pub enum TxArguments {
+    Json(serde_json::Map<String, Value>),
+    Bytes(Vec<u8>),
+}
+
+impl TxArguments {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, HelperError> {
+        match self {
+            TxArguments::Json(args) => {
+                let ret = serde_json::to_vec(args)
+                    .with_context(|| format!("Failed to serialize JSON. Arguments {:?}", args))?;
+                Ok(ret)
+            }
+            TxArguments::Bytes(bytes) => Ok(bytes.clone()),
+        }
+    }
+}
+
+impl From<serde_json::Map<String, Value>> for TxArguments {
+    fn from(args: serde_json::Map<String, Value>) -> Self {
+        TxArguments::Json(args)
+    }
+}
+
+impl From<Vec<u8>> for TxArguments {
+    fn from(bytes: Vec<u8>) -> Self {
+        TxArguments::Bytes(bytes)
+    }
 }
 
 #[derive(Debug)]
@@ -21,12 +60,15 @@ pub struct TxWrapper<T> {
     account: Option<String>,
     contract: Option<String>,
     function: String,
-    arguments: serde_json::Map<String, Value>,
+    arguments: TxArguments,
     near: Option<u128>,
     gas: Option<u64>,
+    /// `false` if the function rejects deposits, see `non_payable`.
+    payable: bool,
     tx_kind: TxKind,
     state: Option<State<T>>,
     label: Option<Key>,
+    sandbox_step: Option<SandboxStep<T>>,
 }
 
 impl<T: DevNetwork + Debug> TxWrapper<T> {
@@ -34,20 +76,22 @@ impl<T: DevNetwork + Debug> TxWrapper<T> {
         account: Option<String>,
         contract: Option<String>,
         function: String,
-        arguments: serde_json::Map<String, Value>,
+        arguments: impl Into<TxArguments>,
         tx_kind: TxKind,
         state: State<T>,
     ) -> Self {
         Self {
             account,
             contract,
-            arguments,
+            arguments: arguments.into(),
             function,
             near: None,
             gas: None,
+            payable: true,
             tx_kind,
             state: Some(state),
             label: None,
+            sandbox_step: None,
         }
     }
 
@@ -59,7 +103,7 @@ impl<T: DevNetwork + Debug> TxWrapper<T> {
         self.contract.as_ref()
     }
 
-    pub fn arguments(&self) -> &(impl Deserialize + Serialize + Debug) {
+    pub fn arguments(&self) -> &TxArguments {
         &self.arguments
     }
 
@@ -67,8 +111,8 @@ impl<T: DevNetwork + Debug> TxWrapper<T> {
         self.function.as_str()
     }
 
-    pub fn gas(&self) -> u64 {
-        self.gas.unwrap_or(0)
+    pub fn gas(&self) -> Gas {
+        Gas(self.gas.unwrap_or(0))
     }
 
     pub fn near(&self) -> u128 {
@@ -80,64 +124,208 @@ impl<T: DevNetwork + Debug> TxWrapper<T> {
         self
     }
 
+    /// Marks the function as not payable, so the chain fails before execution if a deposit is attached.
+    /// Generated helpers mark functions which aren't `#[payable]` in the ABI or the source.
+    pub fn non_payable(mut self) -> Self {
+        self.payable = false;
+        self
+    }
+
     pub fn with_gas(mut self, gas: impl Gasable) -> Self {
         self.gas = Some(gas.parse());
         self
     }
 
+    /// Replaces arguments with the borsh serialized `args`.
+    pub fn with_args_borsh(mut self, args: &impl BorshSerialize) -> Result<Self, HelperError> {
+        let bytes = args
+            .try_to_vec()
+            .context("Failed to serialize arguments with borsh")?;
+        self.arguments = TxArguments::Bytes(bytes);
+        Ok(self)
+    }
+
+    /// Replaces arguments with the raw bytes, they are passed to the contract as is.
+    pub fn with_args_raw(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.arguments = TxArguments::Bytes(bytes.into());
+        self
+    }
+
     pub fn then(mut self) -> State<T> {
         let mut state = self.state.take().unwrap();
         state.add_tx_scenario(self);
         state
     }
 
+    /// The account or contract which signs the transaction of the step.
+    pub(crate) fn signer(&self) -> Option<String> {
+        match self.tx_kind {
+            TxKind::AccountCallContract => self.account.clone(),
+            TxKind::SelfContractCall => self.contract.clone(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn context(&self, label: &Key) -> TxContext {
+        TxContext {
+            label: label.to_string(),
+            signer: self.signer(),
+            receiver: self.contract.clone(),
+            method: self.function.clone(),
+        }
+    }
+
+    /// Fails if the deposit is attached to the function which isn't payable.
+    pub(crate) fn check_deposit(&self) -> Result<(), HelperError> {
+        if self.payable || self.near() == 0 {
+            return Ok(());
+        }
+
+        Err(HelperError::TransactionError(format!(
+            "`{}` isn't payable, but {} is attached",
+            self.function,
+            format_near(self.near())
+        )))
+    }
+
     pub(crate) fn label(&self) -> Option<Key> {
         self.label.clone()
     }
 
+    /// Returns the label of the step, the unique label is generated if the step is unlabeled.
+    pub(crate) fn label_or_generate(&mut self) -> String {
+        if let Some(Key::Label(label)) = &self.label {
+            return label.clone();
+        }
+
+        let state = self
+            .state
+            .as_mut()
+            .expect("the step is already added to the state");
+        let label = state.generate_label(&self.function);
+        self.label = Some(Key::Label(label.clone()));
+        label
+    }
+
     pub fn with_label(mut self, label: impl AsRef<str>) -> Self {
         self.label = Some(Key::Label(label.as_ref().to_owned()));
         self
     }
 
+    pub(crate) fn with_sandbox_step(mut self, step: SandboxStep<T>) -> Self {
+        self.sandbox_step = Some(step);
+        self
+    }
+
     pub fn and() -> Self {
         todo!()
     }
 
     pub async fn execute(self) -> Result<ChainResult<T>, HelperError> {
-        let mut state = self.then();
-        let mut ret = ChainResult::new();
+        let state = self.then();
+        execute_steps(state, ChainResult::new(None)).await
+    }
+
+    /// Executes the chain and fetches balances and storage usage of all accounts and contracts
+    /// before the chain and after every step, so `ChainResult::balance_changes` is available.
+    /// It costs a request per account for every step, so it's off for `execute`.
+    pub async fn execute_tracked(self) -> Result<ChainResult<T>, HelperError> {
+        let state = self.then();
+        let snapshot = state.snapshot().await;
+        execute_steps(state, ChainResult::new(Some(snapshot))).await
+    }
+}
+
+/// Executes steps of the state and appends their results to `ret`.
+/// Unlabeled steps continue the numbering of steps which are already in `ret`,
+/// balances are fetched after every step if `ret` is tracked.
+/// Nothing is executed if labels of the steps are repeated or already taken by `ret`,
+/// or if a deposit is attached to the function which isn't payable.
+pub(crate) async fn execute_steps<T: DevNetwork + Debug>(
+    mut state: State<T>,
+    mut ret: ChainResult<T>,
+) -> Result<ChainResult<T>, HelperError> {
+    let offset = ret.len();
+    let txs = state.take_tx_scenarios();
+    let labels = txs
+        .iter()
+        .enumerate()
+        .map(|(idx, tx)| tx.label().unwrap_or(Key::Index(offset + idx)))
+        .collect::<Vec<_>>();
+    ret.check_new_keys(&labels)?;
+    for tx in &txs {
+        tx.check_deposit()?;
+    }
 
-        for (idx, tx) in state.take_tx_scenarios().iter().enumerate() {
-            let label = tx.label().unwrap_or(Key::Index(idx));
-            let tx_details = process_tx(tx, &state).await?;
-            ret.add_tx_details(label, tx_details);
+    for (tx, label) in txs.iter().zip(labels) {
+        let tx_details = process_tx(tx, &state, &label).await?;
+        if ret.is_tracked() {
+            let step_balances = StepBalances {
+                snapshot: state.snapshot().await,
+                signer: tx.signer(),
+                gas_cost: tx_details.gas_cost(),
+            };
+            ret.add_step_balances(step_balances);
         }
-        ret.add_state(state);
-        Ok(ret)
+        let step_info = StepInfo {
+            context: tx.context(&label),
+            deposit: tx.near(),
+        };
+        ret.add_tx_details(label, tx_details);
+        ret.add_step_info(step_info);
     }
+    ret.add_state(state);
+    Ok(ret)
 }
 
 async fn process_tx<T: DevNetwork + Debug>(
     tx: &TxWrapper<T>,
     state: &State<T>,
+    label: &Key,
 ) -> Result<TxDetails, HelperError> {
     let account = tx.account().and_then(|a| state.account(a).ok());
     let contract = tx.contract().and_then(|c| state.contract(c).ok());
-    let tx_error = || format!("Failed to process transaction. Transaction details: {tx:?}");
+    let classify = |e: anyhow::Error| HelperError::from_tx_error(e, tx.context(label));
+    let in_step = |e: HelperError| e.in_step(tx.context(label));
 
     match tx.tx_kind {
         TxKind::ViewAccount => {
-            let account = account.ok_or_else(|| {
+            let id = tx.account().ok_or_else(|| {
                 HelperError::TransactionError(
-                    "the provided account hasn't found or doesn't exist in state.".to_owned(),
+                    "the account id for view hasn't been provided.".to_owned(),
                 )
             })?;
 
-            let ret = account.view_account(state.worker()).await?;
+            let ret = state.view_account_details(id).await.map_err(in_step)?;
             Ok(TxDetails::ViewAccount(ret))
         }
 
+        TxKind::ViewState => {
+            let contract = contract.ok_or_else(|| {
+                HelperError::TransactionError(
+                    "the provided contract hasn't found or doesn't exist in state.".to_owned(),
+                )
+            })?;
+
+            let prefix = tx.arguments().to_bytes()?;
+            let ret = contract
+                .view_state(state.worker(), Some(&prefix))
+                .await
+                .map_err(classify)?;
+
+            Ok(TxDetails::ViewState(StorageView::new(ret)))
+        }
+
+        TxKind::Patch | TxKind::WaitBlocks => {
+            let step = tx.sandbox_step.as_ref().ok_or_else(|| {
+                HelperError::TransactionError(
+                    "the sandbox operation hasn't been provided.".to_owned(),
+                )
+            })?;
+
+            step.run(state).await.map_err(in_step)
+        }
+
         TxKind::AccountCallContract => {
             let account = account.ok_or_else(|| {
                 HelperError::TransactionError(
@@ -154,12 +342,11 @@ async fn process_tx<T: DevNetwork + Debug>(
             let ret = account
                 .call(state.worker(), contract.id(), tx.function())
                 .deposit(tx.near())
-                .gas(tx.gas())
-                .args_json(tx.arguments())
-                .with_context(|| format!("Failed to parse JSON. Arguments {:?}", tx.arguments()))?
+                .gas(tx.gas().parse())
+                .args(tx.arguments().to_bytes()?)
                 .transact()
                 .await
-                .with_context(tx_error)?;
+                .map_err(classify)?;
 
             Ok(TxDetails::Call(Box::new(ret)))
         }
@@ -172,12 +359,11 @@ async fn process_tx<T: DevNetwork + Debug>(
 
             let ret = contract
                 .call(state.worker(), tx.function())
-                .gas(tx.gas())
-                .args_json(tx.arguments())
-                .with_context(|| format!("Failed to parse JSON. Arguments {:?}", tx.arguments()))?
+                .gas(tx.gas().parse())
+                .args(tx.arguments().to_bytes()?)
                 .view()
                 .await
-                .with_context(tx_error)?;
+                .map_err(classify)?;
 
             Ok(TxDetails::View(ret))
         }
@@ -191,12 +377,11 @@ async fn process_tx<T: DevNetwork + Debug>(
             let ret = contract
                 .call(state.worker(), tx.function())
                 .deposit(tx.near())
-                .gas(tx.gas())
-                .args_json(tx.arguments())
-                .with_context(|| format!("Failed to parse JSON. Arguments {:?}", tx.arguments()))?
+                .gas(tx.gas().parse())
+                .args(tx.arguments().to_bytes()?)
                 .transact()
                 .await
-                .with_context(tx_error)?;
+                .map_err(classify)?;
 
             Ok(TxDetails::Call(Box::new(ret)))
         }
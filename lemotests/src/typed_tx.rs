@@ -0,0 +1,161 @@
+use crate::{ChainResult, Gasable, HelperError, Nearable, State, TxDetails, TxWrapper};
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::de::DeserializeOwned;
+use std::fmt::{self, Debug};
+use workspaces::DevNetwork;
+
+type Decoder<R> = fn(&TxDetails) -> anyhow::Result<R>;
+
+/// The step which knows the type of its result, it is returned by generated helpers
+/// of functions with `"returns"` in the schema.
+///
+/// ```ignore
+/// let (token, tx) = state.view_nft_nft_token("1")?.handle();
+/// let result = tx.execute().await?;
+/// let token: Option<Token> = result.get(&token)?;
+/// ```
+pub struct TypedTx<T, R> {
+    tx: TxWrapper<T>,
+    decode: Decoder<R>,
+}
+
+impl<T, R> TypedTx<T, R>
+where
+    T: DevNetwork + Debug,
+{
+    /// The result is decoded from JSON.
+    pub fn json(tx: TxWrapper<T>) -> Self
+    where
+        R: DeserializeOwned,
+    {
+        Self {
+            tx,
+            decode: TxDetails::json::<R>,
+        }
+    }
+
+    /// The result is decoded with borsh, for functions with `#[result_serializer(borsh)]`.
+    pub fn borsh(tx: TxWrapper<T>) -> Self
+    where
+        R: BorshDeserialize,
+    {
+        Self {
+            tx,
+            decode: TxDetails::borsh::<R>,
+        }
+    }
+
+    /// Returns the handle to get the decoded result from `ChainResult` and the step.
+    /// The label is generated if the step doesn't have it.
+    pub fn handle(mut self) -> (Handle<R>, TxWrapper<T>) {
+        let handle = Handle {
+            label: self.tx.label_or_generate(),
+            decode: self.decode,
+        };
+        (handle, self.tx)
+    }
+
+    pub fn into_inner(self) -> TxWrapper<T> {
+        self.tx
+    }
+
+    pub fn with_deposit(self, deposit: impl Nearable) -> Self {
+        self.map(|tx| tx.with_deposit(deposit))
+    }
+
+    pub fn with_gas(self, gas: impl Gasable) -> Self {
+        self.map(|tx| tx.with_gas(gas))
+    }
+
+    pub fn with_label(self, label: impl AsRef<str>) -> Self {
+        self.map(|tx| tx.with_label(label))
+    }
+
+    pub fn with_args_borsh(self, args: &impl BorshSerialize) -> Result<Self, HelperError> {
+        let decode = self.decode;
+        let tx = self.tx.with_args_borsh(args)?;
+        Ok(Self { tx, decode })
+    }
+
+    pub fn with_args_raw(self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.map(|tx| tx.with_args_raw(bytes))
+    }
+
+    pub fn then(self) -> State<T> {
+        self.tx.then()
+    }
+
+    pub async fn execute(self) -> Result<ChainResult<T>, HelperError> {
+        self.tx.execute().await
+    }
+
+    pub async fn execute_tracked(self) -> Result<ChainResult<T>, HelperError> {
+        self.tx.execute_tracked().await
+    }
+
+    fn map(self, f: impl FnOnce(TxWrapper<T>) -> TxWrapper<T>) -> Self {
+        Self {
+            tx: f(self.tx),
+            decode: self.decode,
+        }
+    }
+}
+
+impl<T, R> From<TypedTx<T, R>> for TxWrapper<T> {
+    fn from(typed: TypedTx<T, R>) -> Self {
+        typed.tx
+    }
+}
+
+impl<T: Debug, R> Debug for TypedTx<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TypedTx")
+            .field("tx", &self.tx)
+            .field("result", &std::any::type_name::<R>())
+            .finish()
+    }
+}
+
+/// The label of the step and the type of its result, see `TypedTx::handle`.
+pub struct Handle<R> {
+    label: String,
+    decode: Decoder<R>,
+}
+
+impl<R> Handle<R> {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<R> Clone for Handle<R> {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label.clone(),
+            decode: self.decode,
+        }
+    }
+}
+
+impl<R> Debug for Handle<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("label", &self.label)
+            .field("result", &std::any::type_name::<R>())
+            .finish()
+    }
+}
+
+impl<T> ChainResult<T> {
+    /// Returns the decoded result of the step of the handle.
+    pub fn get<R>(&self, handle: &Handle<R>) -> Result<R, HelperError> {
+        let details = self.tx(&handle.label)?;
+        (handle.decode)(details).map_err(|e| {
+            HelperError::ChainResultError(format!(
+                "Failed to decode the result of {} into `{}`: {e}",
+                handle.label,
+                std::any::type_name::<R>()
+            ))
+        })
+    }
+}
@@ -1,14 +1,109 @@
+use crate::{HelperError, STORAGE_PRICE_PER_BYTE};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
 use std::fmt;
-use std::ops::Sub;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
 
-#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug)]
+const YOCTO_PER_NEAR: u128 = 10u128.pow(24);
+const YOCTO_PER_MILLI_NEAR: u128 = 10u128.pow(21);
+const YOCTO_PER_MICRO_NEAR: u128 = 10u128.pow(18);
+pub(crate) const GAS_PER_TGAS: u64 = 10u64.pow(12);
+const GAS_PER_GGAS: u64 = 10u64.pow(9);
+
+/// The amount of whole NEAR, e.g. `Near(5)` is 5 NEAR.
+/// Fractional amounts are `YoctoNear`, created with `Near::milli`, `Near::micro`, `Near::yocto`
+/// or parsed from strings like `"1.25 N"` or `"500 mN"`.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug, Default)]
 pub struct Near(pub u128);
 
+/// Raw yocto NEAR, fractional amounts and the result of arithmetic with `Near` which may be fractional.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct YoctoNear(pub u128);
+
+impl Near {
+    pub fn milli(milli: u128) -> YoctoNear {
+        milli
+            .checked_mul(YOCTO_PER_MILLI_NEAR)
+            .map(YoctoNear)
+            .unwrap_or_else(|| panic!("{milli} milli NEAR overflows u128"))
+    }
+
+    pub fn micro(micro: u128) -> YoctoNear {
+        micro
+            .checked_mul(YOCTO_PER_MICRO_NEAR)
+            .map(YoctoNear)
+            .unwrap_or_else(|| panic!("{micro} micro NEAR overflows u128"))
+    }
+
+    pub const fn yocto(yocto: u128) -> YoctoNear {
+        YoctoNear(yocto)
+    }
+
+    pub fn as_yocto(&self) -> u128 {
+        self.parse()
+    }
+
+    pub fn checked_add(self, other: Near) -> Option<Near> {
+        self.0.checked_add(other.0).map(Near)
+    }
+
+    pub fn checked_sub(self, other: Near) -> Option<Near> {
+        self.0.checked_sub(other.0).map(Near)
+    }
+
+    pub fn checked_mul(self, other: u128) -> Option<Near> {
+        self.0.checked_mul(other).map(Near)
+    }
+
+    pub fn checked_div(self, other: u128) -> Option<YoctoNear> {
+        YoctoNear::from(self).checked_div(other)
+    }
+}
+
+impl YoctoNear {
+    pub fn as_yocto(&self) -> u128 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: impl Nearable) -> Option<YoctoNear> {
+        self.0.checked_add(other.parse()).map(YoctoNear)
+    }
+
+    pub fn checked_sub(self, other: impl Nearable) -> Option<YoctoNear> {
+        self.0.checked_sub(other.parse()).map(YoctoNear)
+    }
+
+    pub fn checked_mul(self, other: u128) -> Option<YoctoNear> {
+        self.0.checked_mul(other).map(YoctoNear)
+    }
+
+    pub fn checked_div(self, other: u128) -> Option<YoctoNear> {
+        self.0.checked_div(other).map(YoctoNear)
+    }
+}
+
+impl From<Near> for YoctoNear {
+    fn from(near: Near) -> Self {
+        YoctoNear(near.parse())
+    }
+}
+
+impl Add<Near> for Near {
+    type Output = Near;
+
+    fn add(self, other: Near) -> Self::Output {
+        self.checked_add(other)
+            .unwrap_or_else(|| panic!("Overflow while adding {other} to {self}"))
+    }
+}
+
 impl Sub<Near> for Near {
     type Output = Near;
 
     fn sub(self, other: Near) -> Self::Output {
-        Near(self.0.parse() - other.0.parse())
+        self.checked_sub(other)
+            .unwrap_or_else(|| panic!("Overflow while subtracting {other} from {self}"))
     }
 }
 
@@ -16,48 +111,280 @@ impl Sub<u128> for Near {
     type Output = u128;
 
     fn sub(self, other: u128) -> Self::Output {
-        self.parse() - other
+        self.parse()
+            .checked_sub(other)
+            .unwrap_or_else(|| panic!("Overflow while subtracting {other} yocto from {self}"))
     }
 }
 
-impl PartialEq<Near> for u128 {
-    fn eq(&self, other: &Near) -> bool {
-        self.eq(&other.parse())
+impl Mul<u128> for Near {
+    type Output = Near;
+
+    fn mul(self, other: u128) -> Self::Output {
+        self.checked_mul(other)
+            .unwrap_or_else(|| panic!("Overflow while multiplying {self} by {other}"))
     }
 }
 
-impl PartialEq<u128> for Near {
-    fn eq(&self, other: &u128) -> bool {
-        self.parse().eq(other)
+impl Div<u128> for Near {
+    type Output = YoctoNear;
+
+    fn div(self, other: u128) -> Self::Output {
+        self.checked_div(other)
+            .unwrap_or_else(|| panic!("Failed to divide {self} by zero"))
     }
 }
 
-impl PartialOrd<Near> for u128 {
-    fn partial_cmp(&self, other: &Near) -> Option<std::cmp::Ordering> {
-        self.partial_cmp(&other.parse())
+macro_rules! impl_near_ops {
+    ($lhs:ty => $($rhs:ty),+) => {
+        $(
+            impl Add<$rhs> for $lhs {
+                type Output = YoctoNear;
+
+                fn add(self, other: $rhs) -> Self::Output {
+                    YoctoNear(self.parse())
+                        .checked_add(other)
+                        .unwrap_or_else(|| panic!("Overflow while adding {other} to {self}"))
+                }
+            }
+
+            impl Sub<$rhs> for $lhs {
+                type Output = YoctoNear;
+
+                fn sub(self, other: $rhs) -> Self::Output {
+                    YoctoNear(self.parse())
+                        .checked_sub(other)
+                        .unwrap_or_else(|| panic!("Overflow while subtracting {other} from {self}"))
+                }
+            }
+        )+
+    };
+}
+
+impl_near_ops!(Near => YoctoNear);
+impl_near_ops!(YoctoNear => YoctoNear, Near, u128);
+
+impl Mul<u128> for YoctoNear {
+    type Output = YoctoNear;
+
+    fn mul(self, other: u128) -> Self::Output {
+        self.checked_mul(other)
+            .unwrap_or_else(|| panic!("Overflow while multiplying {self} by {other}"))
     }
 }
 
-impl PartialOrd<u128> for Near {
-    fn partial_cmp(&self, other: &u128) -> Option<std::cmp::Ordering> {
-        self.parse().partial_cmp(other)
+impl Div<u128> for YoctoNear {
+    type Output = YoctoNear;
+
+    fn div(self, other: u128) -> Self::Output {
+        self.checked_div(other)
+            .unwrap_or_else(|| panic!("Failed to divide {self} by zero"))
     }
 }
 
+macro_rules! impl_near_cmp {
+    ($(($lhs:ty, $rhs:ty)),+) => {
+        $(
+            impl PartialEq<$rhs> for $lhs {
+                fn eq(&self, other: &$rhs) -> bool {
+                    self.parse().eq(&other.parse())
+                }
+            }
+
+            impl PartialOrd<$rhs> for $lhs {
+                fn partial_cmp(&self, other: &$rhs) -> Option<std::cmp::Ordering> {
+                    self.parse().partial_cmp(&other.parse())
+                }
+            }
+        )+
+    };
+}
+
+impl_near_cmp!(
+    (u128, Near),
+    (Near, u128),
+    (u128, YoctoNear),
+    (YoctoNear, u128),
+    (Near, YoctoNear),
+    (YoctoNear, Near)
+);
+
+/// Raw gas units, the result of arithmetic with `Tgas` and `Ggas`.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Gas(pub u64);
+
 #[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug)]
 pub struct Tgas(pub u64);
 
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug)]
+pub struct Ggas(pub u64);
+
+impl Gas {
+    pub fn checked_add(self, other: impl Gasable) -> Option<Gas> {
+        self.0.checked_add(other.parse()).map(Gas)
+    }
+
+    pub fn checked_sub(self, other: impl Gasable) -> Option<Gas> {
+        self.0.checked_sub(other.parse()).map(Gas)
+    }
+
+    pub fn checked_mul(self, other: u64) -> Option<Gas> {
+        self.0.checked_mul(other).map(Gas)
+    }
+
+    pub fn checked_div(self, other: u64) -> Option<Gas> {
+        self.0.checked_div(other).map(Gas)
+    }
+}
+
+impl From<u64> for Gas {
+    fn from(gas: u64) -> Self {
+        Gas(gas)
+    }
+}
+
+impl From<Tgas> for Gas {
+    fn from(tgas: Tgas) -> Self {
+        Gas(tgas.parse())
+    }
+}
+
+impl From<Ggas> for Gas {
+    fn from(ggas: Ggas) -> Self {
+        Gas(ggas.parse())
+    }
+}
+
+macro_rules! impl_gas_ops {
+    ($lhs:ty => $($rhs:ty),+) => {
+        $(
+            impl Add<$rhs> for $lhs {
+                type Output = Gas;
+
+                fn add(self, other: $rhs) -> Self::Output {
+                    Gas(self.parse())
+                        .checked_add(other)
+                        .unwrap_or_else(|| panic!("Overflow while adding {other} to {self}"))
+                }
+            }
+
+            impl Sub<$rhs> for $lhs {
+                type Output = Gas;
+
+                fn sub(self, other: $rhs) -> Self::Output {
+                    Gas(self.parse())
+                        .checked_sub(other)
+                        .unwrap_or_else(|| panic!("Overflow while subtracting {other} from {self}"))
+                }
+            }
+        )+
+
+        impl Mul<u64> for $lhs {
+            type Output = Gas;
+
+            fn mul(self, other: u64) -> Self::Output {
+                Gas(self.parse())
+                    .checked_mul(other)
+                    .unwrap_or_else(|| panic!("Overflow while multiplying {self} by {other}"))
+            }
+        }
+
+        impl Div<u64> for $lhs {
+            type Output = Gas;
+
+            fn div(self, other: u64) -> Self::Output {
+                Gas(self.parse())
+                    .checked_div(other)
+                    .unwrap_or_else(|| panic!("Failed to divide {self} by zero"))
+            }
+        }
+    };
+}
+
+impl_gas_ops!(Gas => Gas, Tgas, Ggas, u64);
+impl_gas_ops!(Tgas => Gas, Tgas, Ggas, u64);
+impl_gas_ops!(Ggas => Gas, Tgas, Ggas, u64);
+
+/// Bytes of the account storage, converted to NEAR at the protocol storage price,
+/// e.g. `with_deposit(StorageBytes(800))` attaches the deposit for 800 bytes.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct StorageBytes(pub u64);
+
+impl StorageBytes {
+    /// Bytes taken by the account between two `storage_usage` values,
+    /// zero if the storage has been released.
+    pub fn between(before: u64, after: u64) -> Self {
+        Self(after.saturating_sub(before))
+    }
+
+    /// Bytes of the positive storage delta, e.g. the result of `ChainResult::step_storage_delta`.
+    pub fn from_delta(delta: i64) -> Self {
+        Self(delta.max(0) as u64)
+    }
+
+    pub fn to_near(self) -> YoctoNear {
+        YoctoNear(self.parse())
+    }
+}
+
+impl Add<StorageBytes> for StorageBytes {
+    type Output = StorageBytes;
+
+    fn add(self, rhs: StorageBytes) -> Self::Output {
+        self.0
+            .checked_add(rhs.0)
+            .map(StorageBytes)
+            .unwrap_or_else(|| panic!("Overflow while adding {rhs} to {self}"))
+    }
+}
+
+impl Mul<u64> for StorageBytes {
+    type Output = StorageBytes;
+
+    fn mul(self, rhs: u64) -> Self::Output {
+        self.0
+            .checked_mul(rhs)
+            .map(StorageBytes)
+            .unwrap_or_else(|| panic!("Overflow while multiplying {self} by {rhs}"))
+    }
+}
+
+impl From<StorageBytes> for YoctoNear {
+    fn from(bytes: StorageBytes) -> Self {
+        bytes.to_near()
+    }
+}
+
 pub trait Nearable {
     fn parse(&self) -> u128;
 }
 
+/// Amounts of yocto NEAR which can be negative, e.g. balance deltas.
+pub trait SignedNearable {
+    fn parse_signed(&self) -> i128;
+}
+
 pub trait Gasable {
     fn parse(&self) -> u64;
 }
 
 impl Nearable for Near {
     fn parse(&self) -> u128 {
-        self.0 * 10u128.pow(24)
+        self.0
+            .checked_mul(YOCTO_PER_NEAR)
+            .unwrap_or_else(|| panic!("{} NEAR overflows u128", self.0))
+    }
+}
+
+impl Nearable for YoctoNear {
+    fn parse(&self) -> u128 {
+        self.0
+    }
+}
+
+impl Nearable for StorageBytes {
+    fn parse(&self) -> u128 {
+        u128::from(self.0) * STORAGE_PRICE_PER_BYTE
     }
 }
 
@@ -67,9 +394,38 @@ impl Nearable for u128 {
     }
 }
 
+impl<N: Nearable> SignedNearable for N {
+    fn parse_signed(&self) -> i128 {
+        let yocto = self.parse();
+        i128::try_from(yocto).unwrap_or_else(|_| panic!("{yocto} yocto NEAR overflows i128"))
+    }
+}
+
+impl SignedNearable for i128 {
+    fn parse_signed(&self) -> i128 {
+        *self
+    }
+}
+
+impl Gasable for Gas {
+    fn parse(&self) -> u64 {
+        self.0
+    }
+}
+
 impl Gasable for Tgas {
     fn parse(&self) -> u64 {
-        self.0 * 10u64.pow(12)
+        self.0
+            .checked_mul(GAS_PER_TGAS)
+            .unwrap_or_else(|| panic!("{self} overflows u64"))
+    }
+}
+
+impl Gasable for Ggas {
+    fn parse(&self) -> u64 {
+        self.0
+            .checked_mul(GAS_PER_GGAS)
+            .unwrap_or_else(|| panic!("{self} overflows u64"))
     }
 }
 
@@ -81,12 +437,410 @@ impl Gasable for u64 {
 
 impl fmt::Display for Near {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.parse())
+        write!(f, "{} N", self.0)
+    }
+}
+
+impl fmt::Display for YoctoNear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_near(self.0))
+    }
+}
+
+impl FromStr for YoctoNear {
+    type Err = HelperError;
+
+    /// Parses amounts like `"1.25 N"`, `"500 mN"`, `"10 µN"` or `"1 yN"`.
+    /// The amount without the unit is treated as NEAR.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (amount, unit) = split_unit(s);
+        let decimals = match unit {
+            "" | "N" | "NEAR" => 24,
+            "mN" => 21,
+            "µN" | "uN" => 18,
+            "yN" | "yocto" => 0,
+            unit => {
+                return Err(HelperError::ParseError(format!(
+                    "Unknown unit `{unit}` of NEAR amount `{s}`"
+                )))
+            }
+        };
+
+        parse_decimal(amount, decimals)
+            .map(YoctoNear)
+            .ok_or_else(|| HelperError::ParseError(format!("Invalid NEAR amount `{s}`")))
+    }
+}
+
+impl FromStr for Near {
+    type Err = HelperError;
+
+    /// Parses whole amounts like `"5 N"` or `"5000 mN"`, see `YoctoNear` for fractional amounts.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let yocto = s.parse::<YoctoNear>()?.0;
+        if yocto % YOCTO_PER_NEAR != 0 {
+            return Err(HelperError::ParseError(format!(
+                "`{}` isn't a whole number of NEAR",
+                s.trim()
+            )));
+        }
+
+        Ok(Near(yocto / YOCTO_PER_NEAR))
+    }
+}
+
+impl fmt::Display for Gas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let gas = u128::from(self.0);
+        if self.0 >= GAS_PER_TGAS {
+            write!(f, "{} Tgas", format_decimal(gas, 12))
+        } else if self.0 >= GAS_PER_GGAS {
+            write!(f, "{} Ggas", format_decimal(gas, 9))
+        } else {
+            write!(f, "{} gas", self.0)
+        }
+    }
+}
+
+impl fmt::Display for StorageBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} B", self.0)
     }
 }
 
 impl fmt::Display for Tgas {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0.parse())
+        write!(f, "{} Tgas", self.0)
+    }
+}
+
+impl fmt::Display for Ggas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} Ggas", self.0)
+    }
+}
+
+impl FromStr for Gas {
+    type Err = HelperError;
+
+    /// Parses amounts like `"30 Tgas"`, `"2.5 Tgas"`, `"500 Ggas"` or `"1000 gas"`.
+    /// The amount without the unit is treated as raw gas.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (amount, unit) = split_unit(s);
+        let decimals = match unit {
+            "Tgas" | "TGas" => 12,
+            "Ggas" | "GGas" => 9,
+            "" | "gas" => 0,
+            unit => {
+                return Err(HelperError::ParseError(format!(
+                    "Unknown unit `{unit}` of gas amount `{s}`"
+                )))
+            }
+        };
+
+        parse_decimal(amount, decimals)
+            .and_then(|gas| u64::try_from(gas).ok())
+            .map(Gas)
+            .ok_or_else(|| HelperError::ParseError(format!("Invalid gas amount `{s}`")))
+    }
+}
+
+/// NEAR types are encoded as a string with the amount of yocto NEAR, like near-sdk's `U128`.
+impl Serialize for YoctoNear {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for YoctoNear {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let yocto = String::deserialize(deserializer)?;
+        yocto.parse().map(YoctoNear).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Near {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.parse().to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Near {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let yocto = YoctoNear::deserialize(deserializer)?.0;
+        if yocto % YOCTO_PER_NEAR != 0 {
+            return Err(de::Error::custom(format!(
+                "{yocto} isn't a whole number of Near"
+            )));
+        }
+
+        Ok(Near(yocto / YOCTO_PER_NEAR))
+    }
+}
+
+/// Gas types are encoded as a string with the amount of raw gas, like near-sdk's `U64`.
+macro_rules! impl_gas_serde {
+    ($($ty:ident => $gas_per_unit:expr),+) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(&self.parse().to_string())
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let gas: u64 = String::deserialize(deserializer)?
+                        .parse()
+                        .map_err(de::Error::custom)?;
+
+                    let ret = $ty(gas / $gas_per_unit);
+                    if ret.parse() != gas {
+                        return Err(de::Error::custom(format!(
+                            "{gas} isn't a whole number of {}",
+                            stringify!($ty)
+                        )));
+                    }
+
+                    Ok(ret)
+                }
+            }
+        )+
+    };
+}
+
+impl_gas_serde!(Gas => 1, Tgas => GAS_PER_TGAS, Ggas => GAS_PER_GGAS);
+
+/// Splits strings like `"1.25 N"` into the amount and the unit.
+fn split_unit(s: &str) -> (&str, &str) {
+    let unit_start = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (amount, unit) = s.split_at(unit_start);
+    (amount, unit.trim())
+}
+
+/// Parses decimal `amount` into an integer scaled by `10^decimals`.
+/// Returns `None` if the amount is malformed, too precise or too large.
+fn parse_decimal(amount: &str, decimals: u32) -> Option<u128> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return None;
+    }
+
+    let scale = 10u128.pow(decimals);
+    let fraction_scale = 10u128.pow(decimals - fraction.len() as u32);
+    let fraction = match fraction {
+        "" => 0,
+        fraction => fraction.parse::<u128>().ok()? * fraction_scale,
+    };
+
+    whole
+        .parse::<u128>()
+        .ok()?
+        .checked_mul(scale)?
+        .checked_add(fraction)
+}
+
+/// Formats `value` scaled by `10^decimals` as a decimal number without trailing zeros.
+fn format_decimal(value: u128, decimals: u32) -> String {
+    let scale = 10u128.pow(decimals);
+    let (whole, fraction) = (value / scale, value % scale);
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{fraction:0width$}", width = decimals as usize);
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+/// Formats yocto NEAR as a human-readable amount of NEAR, e.g. `1.25 N`.
+pub fn format_near(yocto: u128) -> String {
+    format!("{} N", format_decimal(yocto, 24))
+}
+
+/// Formats signed yocto NEAR, e.g. `-1.25 N`.
+pub fn format_near_signed(yocto: i128) -> String {
+    let sign = if yocto < 0 { "-" } else { "" };
+    format!("{sign}{}", format_near(yocto.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_constructors_work() {
+        assert_eq!(Near(5).parse(), 5 * 10u128.pow(24));
+        assert_eq!(Near(5).0, 5);
+        assert_eq!(Near::milli(500), Near(1) / 2);
+        assert_eq!(Near::micro(1).as_yocto(), 10u128.pow(18));
+        assert_eq!(Near::yocto(1).parse(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows u128")]
+    fn near_constructor_overflow_panics() {
+        let _ = Near::milli(u128::MAX / 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows i128")]
+    fn near_parse_signed_overflow_panics() {
+        let _ = YoctoNear(u128::MAX).parse_signed();
+    }
+
+    #[test]
+    fn near_arithmetic_works() {
+        assert_eq!(Near(2) + Near::milli(500), Near::milli(2500));
+        assert_eq!(Near(2) - Near(1), Near(1));
+        assert_eq!(Near(2) * 3, Near(6));
+        assert_eq!(Near::milli(250) * 4, Near(1));
+        assert_eq!(Near(1) - Near::milli(250), Near::milli(750));
+        assert_eq!(Near(5) - 1, Near(5).parse() - 1);
+        assert!(Near(1).checked_sub(Near(2)).is_none());
+        assert!(Near::yocto(u128::MAX).checked_add(Near::yocto(1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn near_sub_overflow_panics() {
+        let _ = Near(1) - Near(2);
+    }
+
+    #[test]
+    fn near_from_str_works() {
+        assert_eq!("1.25 N".parse::<YoctoNear>().unwrap(), Near::milli(1250));
+        assert_eq!("500 mN".parse::<YoctoNear>().unwrap(), Near::milli(500));
+        assert_eq!("10µN".parse::<YoctoNear>().unwrap(), Near::micro(10));
+        assert_eq!("1 yN".parse::<YoctoNear>().unwrap(), Near::yocto(1));
+        assert_eq!("0.10 NEAR".parse::<YoctoNear>().unwrap(), Near::milli(100));
+        assert_eq!("5".parse::<Near>().unwrap(), Near(5));
+        assert_eq!("5000 mN".parse::<Near>().unwrap(), Near(5));
+        assert!("1.25 N".parse::<Near>().is_err());
+        assert!("1.5 yN".parse::<YoctoNear>().is_err());
+        assert!("1.2.3 N".parse::<YoctoNear>().is_err());
+        assert!("five N".parse::<YoctoNear>().is_err());
+        assert!("1 kN".parse::<YoctoNear>().is_err());
+    }
+
+    #[test]
+    fn near_display_works() {
+        assert_eq!(Near(5).to_string(), "5 N");
+        assert_eq!(Near::milli(1250).to_string(), "1.25 N");
+        assert_eq!(
+            Near::milli(1250).to_string().parse::<YoctoNear>().unwrap(),
+            Near::milli(1250)
+        );
+    }
+
+    #[test]
+    fn gas_arithmetic_works() {
+        assert_eq!(Tgas(2) + Ggas(500), Gas(2_500_000_000_000));
+        assert_eq!(Tgas(30) - Tgas(10), Gas::from(Tgas(20)));
+        assert_eq!(Tgas(30) / 3, Gas::from(Tgas(10)));
+        assert_eq!(Ggas(1) * 1000, Gas::from(Tgas(1)));
+        assert_eq!(Gas(10) + 5, Gas(15));
+        assert!(Gas(1).checked_sub(Tgas(1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn gas_sub_overflow_panics() {
+        let _ = Ggas(1) - Tgas(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows u64")]
+    fn tgas_overflow_panics() {
+        let _ = Tgas(u64::MAX / 1000).parse();
+    }
+
+    #[test]
+    fn gas_from_str_works() {
+        assert_eq!("30 Tgas".parse::<Gas>().unwrap(), Gas::from(Tgas(30)));
+        assert_eq!("2.5 Tgas".parse::<Gas>().unwrap(), Tgas(2) + Ggas(500));
+        assert_eq!("500Ggas".parse::<Gas>().unwrap(), Gas::from(Ggas(500)));
+        assert_eq!("1000 gas".parse::<Gas>().unwrap(), Gas(1000));
+        assert_eq!("1000".parse::<Gas>().unwrap(), Gas(1000));
+        assert!("1.5 gas".parse::<Gas>().is_err());
+        assert!("30 Pgas".parse::<Gas>().is_err());
+        assert!("100000000 Tgas".parse::<Gas>().is_err());
+    }
+
+    #[test]
+    fn gas_display_works() {
+        assert_eq!((Tgas(2) + Ggas(500)).to_string(), "2.5 Tgas");
+        assert_eq!(Gas::from(Ggas(20)).to_string(), "20 Ggas");
+        assert_eq!(Gas(1000).to_string(), "1000 gas");
+        assert_eq!(Tgas(30).to_string(), "30 Tgas");
+    }
+
+    #[test]
+    fn near_serde_works() {
+        let json = serde_json::to_value(Near::milli(1250)).unwrap();
+        assert_eq!(json, serde_json::json!("1250000000000000000000000"));
+        assert_eq!(
+            serde_json::from_value::<YoctoNear>(json.clone()).unwrap(),
+            Near::milli(1250)
+        );
+        assert!(serde_json::from_value::<Near>(json).is_err());
+        assert_eq!(
+            serde_json::to_value(Near(5)).unwrap(),
+            serde_json::json!("5000000000000000000000000")
+        );
+        assert_eq!(
+            serde_json::from_str::<Near>(r#""5000000000000000000000000""#).unwrap(),
+            Near(5)
+        );
+        assert!(serde_json::from_str::<YoctoNear>("5").is_err());
+    }
+
+    #[test]
+    fn storage_bytes_works() {
+        assert_eq!(StorageBytes(100_000).to_near(), Near(1));
+        assert_eq!(StorageBytes(1).parse(), STORAGE_PRICE_PER_BYTE);
+        assert_eq!(
+            StorageBytes(200) * 3 + StorageBytes(400),
+            StorageBytes(1000)
+        );
+        assert_eq!(StorageBytes::between(1000, 1800), StorageBytes(800));
+        assert_eq!(StorageBytes::between(1800, 1000), StorageBytes(0));
+        assert_eq!(StorageBytes::from_delta(-5), StorageBytes(0));
+        assert_eq!(StorageBytes(800).to_string(), "800 B");
+    }
+
+    #[test]
+    fn gas_serde_works() {
+        let json = serde_json::to_value(Tgas(30)).unwrap();
+        assert_eq!(json, serde_json::json!("30000000000000"));
+        assert_eq!(serde_json::from_value::<Tgas>(json).unwrap(), Tgas(30));
+        assert_eq!(
+            serde_json::from_str::<Gas>(r#""2500000000000""#).unwrap(),
+            Tgas(2) + Ggas(500)
+        );
+        assert!(serde_json::from_str::<Tgas>(r#""2500000000000""#).is_err());
+        assert_eq!(
+            serde_json::from_str::<Ggas>(r#""2500000000000""#).unwrap(),
+            Ggas(2500)
+        );
+    }
+
+    #[test]
+    fn format_near_works() {
+        assert_eq!(format_near(Near(5).parse()), "5 N");
+        assert_eq!(format_near(0), "0 N");
+        assert_eq!(format_near(1_250_000_000_000_000_000_000_000), "1.25 N");
+        assert_eq!(format_near(1), "0.000000000000000000000001 N");
+        assert_eq!(format_near_signed(-Near(2).parse_signed()), "-2 N");
+        assert_eq!(format_near_signed(10_i128.pow(23)), "0.1 N");
     }
 }
@@ -0,0 +1,31 @@
+use lemotests::{anyhow, tokio, Near, StateBuilder, ALICE};
+
+#[tokio::test]
+async fn balance_is_set_exactly() -> anyhow::Result<()> {
+    let state = StateBuilder::sandbox()
+        .with_alice(Near(10))?
+        .build()
+        .await?;
+
+    let state = state
+        .set_balance(ALICE, Near(20))?
+        .execute()
+        .await?
+        .into_state();
+    assert_eq!(
+        state.view_account_details(ALICE).await?.balance,
+        Near(20).as_yocto()
+    );
+
+    let state = state
+        .set_balance(ALICE, Near(3))?
+        .execute()
+        .await?
+        .into_state();
+    assert_eq!(
+        state.view_account_details(ALICE).await?.balance,
+        Near(3).as_yocto()
+    );
+
+    Ok(())
+}